@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use crate::camera::{CameraPose, WorldCamera};
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct CameraBookmark {
+    pub name: String,
+    #[serde(flatten)]
+    pub pose: CameraPose,
+}
+
+/// Where the scene keeps its bookmarks, relative to the working directory. The scene
+/// isn't saved anywhere else, and its objects come and go, so the file belongs to the
+/// directory the sandbox runs in and can be shared along with it.
+const SCENE_FILE: &str = "scene.cameras.json";
+
+/// Named viewpoints of the scene, kept in a JSON file.
+pub struct Bookmarks {
+    bookmarks: Vec<CameraBookmark>,
+    file: PathBuf,
+    new_name: String,
+    error: Option<String>,
+}
+
+impl Bookmarks {
+    /// The bookmarks saved for the scene, or none when there's no file yet.
+    pub fn new() -> Bookmarks {
+        let mut bookmarks = Bookmarks {
+            bookmarks: Vec::new(),
+            file: PathBuf::from(SCENE_FILE),
+            new_name: String::new(),
+            error: None,
+        };
+        bookmarks.load();
+        bookmarks
+    }
+
+    fn load(&mut self) {
+        let file = &self.file;
+        match std::fs::read_to_string(file) {
+            Ok(json) => match serde_json::from_str(&json) {
+                Ok(bookmarks) => self.bookmarks = bookmarks,
+                Err(err) => self.error = Some(format!("Failed to parse {}: {err}", file.display())),
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => self.error = Some(format!("Failed to read {}: {err}", file.display())),
+        }
+    }
+
+    fn save(&mut self) {
+        let file = &self.file;
+        let json = serde_json::to_string_pretty(&self.bookmarks).expect("bookmarks serialize");
+        self.error = std::fs::write(file, json)
+            .err()
+            .map(|err| format!("Failed to write {}: {err}", file.display()));
+    }
+
+    pub fn build_widget(&mut self, ui: &mut egui::Ui, camera: &mut WorldCamera) {
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.new_name);
+                let name = self.new_name.trim().to_string();
+                if ui
+                    .add_enabled(!name.is_empty(), egui::Button::new("Save view"))
+                    .on_hover_text("Overwrites a bookmark with the same name")
+                    .clicked()
+                {
+                    let bookmark = CameraBookmark {
+                        name,
+                        pose: camera.pose(),
+                    };
+                    match self.bookmarks.iter_mut().find(|b| b.name == bookmark.name) {
+                        Some(existing) => *existing = bookmark,
+                        None => self.bookmarks.push(bookmark),
+                    }
+                    self.new_name.clear();
+                    self.save();
+                }
+            });
+
+            let mut remove = None;
+            for (index, bookmark) in self.bookmarks.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.button(&bookmark.name).clicked() {
+                        camera.transition_to(bookmark.pose);
+                    }
+                    if ui.button("[X]").clicked() {
+                        remove = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = remove {
+                self.bookmarks.remove(index);
+                self.save();
+            }
+
+            ui.weak(format!("Saved in {}", self.file.display()));
+            if let Some(error) = &self.error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+        });
+    }
+}
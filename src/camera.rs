@@ -1,88 +1,497 @@
+#[derive(PartialEq, Debug, Copy, Clone, serde::Serialize, serde::Deserialize)]
+pub enum CameraMode {
+    /// Free movement with WASD, looking around with the mouse.
+    Fly,
+    /// Rotates around `target` at `distance`, with dolly and pan.
+    Orbit,
+}
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Projection {
+    Perspective,
+    Orthographic,
+}
+
+/// Axis-aligned views, named after the side of the scene they look at.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum ViewPreset {
+    Front,
+    Back,
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl ViewPreset {
+    pub const ALL: [ViewPreset; 6] = [
+        ViewPreset::Front,
+        ViewPreset::Back,
+        ViewPreset::Left,
+        ViewPreset::Right,
+        ViewPreset::Top,
+        ViewPreset::Bottom,
+    ];
+
+    /// (yaw, pitch) looking at that side.
+    fn rotation(self) -> (f32, f32) {
+        use std::f32::consts::{FRAC_PI_2, PI};
+        match self {
+            ViewPreset::Front => (0.0, 0.0),
+            ViewPreset::Back => (PI, 0.0),
+            ViewPreset::Left => (FRAC_PI_2, 0.0),
+            ViewPreset::Right => (-FRAC_PI_2, 0.0),
+            ViewPreset::Top => (0.0, FRAC_PI_2),
+            ViewPreset::Bottom => (0.0, -FRAC_PI_2),
+        }
+    }
+}
+
+/// A viewpoint to return to.
+#[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CameraPose {
+    pub position: [f32; 3],
+    pub rotation: [f32; 3], // (yaw, pitch, roll)
+    pub fovy: f32,
+    pub mode: CameraMode,
+}
+
+struct Transition {
+    from: CameraPose,
+    to: CameraPose,
+    elapsed: f32,
+}
+
 pub struct WorldCamera {
+    mode: CameraMode,
+    projection: Projection,
     position: glam::Vec3,
-    rotation: glam::Vec3, // (yaw, pitch, roll)
+    orientation: glam::Quat,
     aspect: f32,
     fovy: f32,
     z_near: f32,
     z_far: f32,
+    ortho_scale: f32,   // half the height of the orthographic view volume
+    jitter: glam::Vec2, // sub-pixel projection offset in NDC
+    target: glam::Vec3, // orbit centre, always `distance` ahead of the camera in orbit mode
+    distance: f32,
+    speed: f32,       // fly speed in units per second
+    sensitivity: f32, // radians per point of mouse movement
+    transition: Option<Transition>,
+    pub roll_enabled: bool,
 }
 
 impl WorldCamera {
-    const CAM_SPEED: f32 = 0.03;
+    /// Fly speed limits, in units per second.
+    const SPEED_RANGE: std::ops::RangeInclusive<f32> = 0.05..=100.0;
+    /// Speed multipliers while holding the sprint and slow modifiers.
+    pub const SPRINT_FACTOR: f32 = 4.0;
+    pub const SLOW_FACTOR: f32 = 0.25;
+    const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.001;
 
     pub fn new() -> WorldCamera {
         WorldCamera {
+            mode: CameraMode::Fly,
+            projection: Projection::Perspective,
             position: glam::vec3(0.0, 0.0, -3.0),
-            rotation: glam::vec3(0.0, 0.0, 0.0),
+            orientation: glam::Quat::IDENTITY,
             aspect: 1.,
             fovy: 90f32.to_radians(),
             z_near: 0.1,
             z_far: 100.0,
+            ortho_scale: 3.0,
+            jitter: glam::Vec2::ZERO,
+            target: glam::Vec3::ZERO,
+            distance: 3.0,
+            speed: 2.0,
+            sensitivity: 0.002,
+            transition: None,
+            roll_enabled: false,
+        }
+    }
+
+    pub fn pose(&self) -> CameraPose {
+        CameraPose {
+            position: self.position.to_array(),
+            rotation: self.yaw_pitch_roll().to_array(),
+            fovy: self.fovy,
+            mode: self.mode,
         }
     }
 
+    /// Glides to `pose` over the next frames, see [`WorldCamera::update`].
+    pub fn transition_to(&mut self, pose: CameraPose) {
+        self.transition = Some(Transition {
+            from: self.pose(),
+            to: pose,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Advances a running transition by `dt` seconds.
+    pub fn update(&mut self, dt: f32) {
+        const TRANSITION_TIME: f32 = 0.6;
+
+        let Some(transition) = &mut self.transition else {
+            return;
+        };
+        transition.elapsed += dt;
+        let t = (transition.elapsed / TRANSITION_TIME).min(1.0);
+        let t = t * t * (3.0 - 2.0 * t);
+        let (from, to) = (transition.from, transition.to);
+
+        let orientation = |pose: CameraPose| {
+            let [yaw, pitch, roll] = pose.rotation;
+            glam::Quat::from_euler(glam::EulerRot::YXZ, yaw, pitch, roll)
+        };
+        self.orientation = orientation(from).slerp(orientation(to), t);
+        self.position = glam::Vec3::from(from.position).lerp(glam::Vec3::from(to.position), t);
+        self.fovy = from.fovy + (to.fovy - from.fovy) * t;
+
+        if t >= 1.0 {
+            self.finish_transition();
+        }
+    }
+
+    /// Stops a running transition where it is, entering the target's mode from there.
+    fn finish_transition(&mut self) {
+        if let Some(transition) = self.transition.take() {
+            self.mode = CameraMode::Fly;
+            self.set_mode(transition.to.mode);
+        }
+    }
+
+    pub fn mode(&self) -> CameraMode {
+        self.mode
+    }
+
+    /// Switches mode keeping the current view. Orbit mode picks up the target at the
+    /// last orbit distance straight ahead.
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        if mode == CameraMode::Orbit && self.mode != CameraMode::Orbit {
+            self.target = self.pivot();
+        }
+        self.mode = mode;
+    }
+
+    /// The point views rotate around: the orbit target, or the point that would become
+    /// it in fly mode.
+    fn pivot(&self) -> glam::Vec3 {
+        match self.mode {
+            CameraMode::Fly => self.position + self.look_direction() * self.distance,
+            CameraMode::Orbit => self.target,
+        }
+    }
+
+    /// Looks at the pivot from the side named by `preset`, at the same distance.
+    pub fn set_view_preset(&mut self, preset: ViewPreset) {
+        self.finish_transition();
+        let pivot = self.pivot();
+        let (yaw, pitch) = preset.rotation();
+        // Top and bottom stop just short of straight up and down, like the mouse does.
+        self.set_yaw_pitch_roll(yaw, pitch, 0.0);
+        self.position = pivot - self.look_direction() * self.distance;
+    }
+
     pub fn build_projection(&self) -> glam::Mat4 {
-        glam::Mat4::perspective_lh(self.fovy, self.aspect, self.z_near, self.z_far)
+        let jitter = glam::Mat4::from_translation(self.jitter.extend(0.0));
+        let projection = match self.projection {
+            Projection::Perspective => {
+                glam::Mat4::perspective_lh(self.fovy, self.aspect, self.z_near, self.z_far)
+            }
+            Projection::Orthographic => {
+                let (half_width, half_height) = (self.ortho_scale * self.aspect, self.ortho_scale);
+                glam::Mat4::orthographic_lh(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.z_near,
+                    self.z_far,
+                )
+            }
+        };
+        jitter * projection
+    }
+
+    pub fn jitter(&self) -> glam::Vec2 {
+        self.jitter
+    }
+
+    /// Offsets the projection by the `index`-th point of an 8 sample Halton(2, 3)
+    /// sequence, within one pixel of a `viewport` sized in pixels.
+    pub fn set_jitter(&mut self, index: u64, viewport: glam::Vec2) {
+        let index = (index % 8 + 1) as u32;
+        let offset = glam::vec2(halton(index, 2), halton(index, 3)) - 0.5;
+        self.jitter = offset * 2.0 / viewport;
+    }
+
+    pub fn clear_jitter(&mut self) {
+        self.jitter = glam::Vec2::ZERO;
     }
 
     pub fn build_view(&self) -> glam::Mat4 {
-        let rotation_matrix = glam::Mat4::from_euler(
-            glam::EulerRot::ZXY,
-            -self.rotation.z,
-            -self.rotation.y,
-            -self.rotation.x,
-        );
-        let translation_matrix = glam::Mat4::from_translation(-self.position);
-        rotation_matrix * translation_matrix
+        glam::Mat4::from_quat(self.orientation.inverse())
+            * glam::Mat4::from_translation(-self.position)
     }
 
     pub fn get_rotation_quat(&self) -> glam::Quat {
-        glam::Quat::from_euler(
-            glam::EulerRot::YXZ,
-            self.rotation.x,
-            self.rotation.y,
-            self.rotation.z,
-        )
+        self.orientation
     }
 
-    pub fn forward(&mut self) {
-        self.position +=
-            self.get_rotation_quat() * glam::Vec3::new(0.0, 0.0, 1.0) * Self::CAM_SPEED;
+    /// The orientation as yaw around world up, then pitch, then roll around the view axis.
+    fn yaw_pitch_roll(&self) -> glam::Vec3 {
+        let (yaw, pitch, roll) = self.orientation.to_euler(glam::EulerRot::YXZ);
+        glam::vec3(yaw, pitch, roll)
     }
 
-    pub fn backward(&mut self) {
-        self.position -=
-            self.get_rotation_quat() * glam::Vec3::new(0.0, 0.0, 1.0) * Self::CAM_SPEED;
+    fn set_yaw_pitch_roll(&mut self, yaw: f32, pitch: f32, roll: f32) {
+        // Clamped just short of straight up and down, past which yaw and roll coincide.
+        let pitch = pitch.clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+        self.orientation = glam::Quat::from_euler(glam::EulerRot::YXZ, yaw, pitch, roll);
     }
 
-    pub fn right(&mut self) {
-        self.position +=
-            self.get_rotation_quat() * glam::Vec3::new(1.0, 0.0, 0.0) * Self::CAM_SPEED;
+    fn look_direction(&self) -> glam::Vec3 {
+        self.get_rotation_quat() * glam::Vec3::new(0.0, 0.0, 1.0)
     }
 
-    pub fn left(&mut self) {
-        self.position -=
-            self.get_rotation_quat() * glam::Vec3::new(1.0, 0.0, 0.0) * Self::CAM_SPEED;
+    fn update_orbit_position(&mut self) {
+        self.position = self.target - self.look_direction() * self.distance;
     }
 
-    pub fn up(&mut self) {
-        self.position +=
-            self.get_rotation_quat() * glam::Vec3::new(0.0, 1.0, 0.0) * Self::CAM_SPEED;
+    fn translate(&mut self, local_direction: glam::Vec3, dt: f32) {
+        self.finish_transition();
+        self.position += self.get_rotation_quat() * local_direction * self.speed * dt;
     }
 
-    pub fn down(&mut self) {
-        self.position -=
-            self.get_rotation_quat() * glam::Vec3::new(0.0, 1.0, 0.0) * Self::CAM_SPEED;
+    pub fn forward(&mut self, dt: f32) {
+        self.translate(glam::Vec3::new(0.0, 0.0, 1.0), dt);
+    }
+
+    pub fn backward(&mut self, dt: f32) {
+        self.translate(glam::Vec3::new(0.0, 0.0, -1.0), dt);
+    }
+
+    pub fn right(&mut self, dt: f32) {
+        self.translate(glam::Vec3::new(1.0, 0.0, 0.0), dt);
+    }
+
+    pub fn left(&mut self, dt: f32) {
+        self.translate(glam::Vec3::new(-1.0, 0.0, 0.0), dt);
+    }
+
+    pub fn up(&mut self, dt: f32) {
+        self.translate(glam::Vec3::new(0.0, 1.0, 0.0), dt);
+    }
+
+    pub fn down(&mut self, dt: f32) {
+        self.translate(glam::Vec3::new(0.0, -1.0, 0.0), dt);
+    }
+
+    /// Scales the fly speed by a scroll `amount`.
+    pub fn adjust_speed(&mut self, amount: f32) {
+        const SCROLL_SPEED_STEP: f32 = 0.005;
+
+        self.speed = (self.speed * (amount * SCROLL_SPEED_STEP).exp())
+            .clamp(*Self::SPEED_RANGE.start(), *Self::SPEED_RANGE.end());
     }
 
     pub fn mouse_moved(&mut self, delta: egui::Vec2) {
-        const MOUSE_SENSETIVITY: f32 = 0.002;
+        self.finish_transition();
+        let angles = self.yaw_pitch_roll();
+        self.set_yaw_pitch_roll(
+            angles.x - delta.x * self.sensitivity,
+            angles.y + delta.y * self.sensitivity,
+            angles.z,
+        );
+        if self.mode == CameraMode::Orbit {
+            self.update_orbit_position();
+        }
+    }
+
+    /// Backs off along the view direction until `bounds` fits the viewport, orbiting
+    /// its centre, and scales clip planes and fly speed to its size.
+    pub fn frame(&mut self, bounds: &crate::object::Bounds) {
+        self.finish_transition();
+        let radius = bounds.radius.max(0.001);
+        let distance = match self.projection {
+            Projection::Perspective => {
+                let half_fovx = ((self.fovy * 0.5).tan() * self.aspect).atan();
+                radius / (self.fovy * 0.5).min(half_fovx).sin()
+            }
+            Projection::Orthographic => {
+                self.ortho_scale = radius * (1.0 / self.aspect).max(1.0);
+                radius * 2.0
+            }
+        };
+
+        self.target = bounds.center;
+        self.distance = distance;
+        self.update_orbit_position();
+        self.z_near = radius * 0.01;
+        self.z_far = (distance + radius) * 10.0;
+        self.speed = radius.clamp(*Self::SPEED_RANGE.start(), *Self::SPEED_RANGE.end());
+    }
+
+    /// Moves towards the orbit target by a scroll `amount`, scaling with the distance.
+    pub fn dolly(&mut self, amount: f32) {
+        self.finish_transition();
+        const DOLLY_SPEED: f32 = 0.005;
+
+        let factor = (-amount * DOLLY_SPEED).exp();
+        self.distance = (self.distance * factor).max(self.z_near);
+        // Moving closer doesn't change an orthographic view, so zoom it instead.
+        if self.projection == Projection::Orthographic {
+            self.ortho_scale *= factor;
+        }
+        self.update_orbit_position();
+    }
+
+    /// Slides the orbit target so it follows a drag of `delta` points across a
+    /// viewport `viewport_height` points high.
+    pub fn pan(&mut self, delta: egui::Vec2, viewport_height: f32) {
+        self.finish_transition();
+        let half_height = match self.projection {
+            Projection::Perspective => self.distance * (self.fovy * 0.5).tan(),
+            Projection::Orthographic => self.ortho_scale,
+        };
+        let scale = 2.0 * half_height / viewport_height;
+        self.target += self.get_rotation_quat() * glam::Vec3::new(-delta.x, delta.y, 0.0) * scale;
+        self.update_orbit_position();
+    }
+
+    /// Turns the camera to face `target` and orbits around it from where it stands.
+    pub fn set_target(&mut self, target: glam::Vec3) {
+        self.finish_transition();
+        let offset = target - self.position;
+        if offset.length() < self.z_near {
+            return;
+        }
+        let direction = offset.normalize();
+        self.set_yaw_pitch_roll(
+            direction.x.atan2(direction.z),
+            (-direction.y).asin(),
+            self.yaw_pitch_roll().z,
+        );
+        self.target = target;
+        self.distance = offset.length();
+    }
+
+    /// Rolls around the view axis, to the right for positive `direction`.
+    pub fn roll(&mut self, direction: f32, dt: f32) {
+        const ROLL_SPEED: f32 = 1.0;
 
-        self.rotation.x -= delta.x * MOUSE_SENSETIVITY;
-        self.rotation.y += delta.y * MOUSE_SENSETIVITY;
+        self.finish_transition();
+        self.orientation *= glam::Quat::from_rotation_z(-direction * ROLL_SPEED * dt);
+    }
+
+    /// Levels the horizon, keeping the view direction.
+    pub fn reset_up(&mut self) {
+        self.finish_transition();
+        let angles = self.yaw_pitch_roll();
+        self.set_yaw_pitch_roll(angles.x, angles.y, 0.0);
     }
 
     pub fn resize(&mut self, aspect: f32) {
         self.aspect = aspect;
     }
+
+    pub fn build_widget(&mut self, ui: &mut egui::Ui) {
+        ui.vertical(|ui| {
+            ui.style_mut().spacing.slider_width = ui.available_width();
+
+            ui.label(format!("fly speed: {:.2}", self.speed));
+            ui.add(egui::Slider::new(&mut self.speed, Self::SPEED_RANGE).logarithmic(true));
+            ui.label(format!("mouse sensitivity: {}", self.sensitivity));
+            ui.add(egui::Slider::new(&mut self.sensitivity, 0.0005..=0.01).logarithmic(true));
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.roll_enabled, "Q/E roll");
+                if ui.button("Reset up").clicked() {
+                    self.reset_up();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("projection:");
+                ui.selectable_value(&mut self.projection, Projection::Perspective, "Perspective");
+                ui.selectable_value(
+                    &mut self.projection,
+                    Projection::Orthographic,
+                    "Orthographic",
+                );
+            });
+            match self.projection {
+                Projection::Perspective => {
+                    ui.label(format!("fov: {:.0}°", self.fovy.to_degrees()));
+                    ui.add(egui::Slider::new(&mut self.fovy, 0.17..=2.97).show_value(false));
+                }
+                Projection::Orthographic => {
+                    ui.label(format!("ortho scale: {:.2}", self.ortho_scale));
+                    ui.add(
+                        egui::Slider::new(&mut self.ortho_scale, 0.01..=100.0).logarithmic(true),
+                    );
+                }
+            }
+            ui.horizontal(|ui| {
+                ui.label("near:");
+                ui.add(
+                    egui::DragValue::new(&mut self.z_near)
+                        .speed(0.01)
+                        .range(0.001..=self.z_far - 0.001),
+                );
+                ui.label("far:");
+                ui.add(
+                    egui::DragValue::new(&mut self.z_far)
+                        .speed(1.0)
+                        .range(self.z_near + 0.001..=10000.0),
+                );
+            });
+
+            ui.horizontal_wrapped(|ui| {
+                ui.label("view:");
+                for preset in ViewPreset::ALL {
+                    if ui.button(format!("{preset:?}")).clicked() {
+                        self.set_view_preset(preset);
+                    }
+                }
+            });
+        });
+    }
+}
+
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut result = 0.0;
+    let mut fraction = 1.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_rotate_like_the_mouse() {
+        // Reading the angles back out of the quaternion can land a rounding error past
+        // the clamp.
+        let max_pitch = WorldCamera::MAX_PITCH + 1e-6;
+        for preset in [ViewPreset::Top, ViewPreset::Bottom] {
+            let mut camera = WorldCamera::new();
+            camera.set_view_preset(preset);
+            let pitch = camera.yaw_pitch_roll().y;
+            assert!(pitch.abs() <= max_pitch, "{preset:?} pitched to {pitch}");
+
+            for delta in [egui::vec2(3.0, 2.0), egui::vec2(3.0, -2.0)] {
+                camera.mouse_moved(delta);
+                let [_, pitch, roll] = camera.yaw_pitch_roll().to_array();
+                assert!(roll.abs() < 1e-4, "{preset:?} rolled by {roll}");
+                assert!(pitch.abs() <= max_pitch, "{preset:?} pitched to {pitch}");
+            }
+        }
+    }
 }
@@ -1,14 +1,53 @@
 pub mod blur;
 pub mod chromatic;
+pub mod color_grading;
+pub mod crt;
+pub mod dither;
+pub mod edge;
+pub mod film_grain;
 pub mod flat;
+pub mod fog;
+pub mod motion_blur;
 pub mod negative;
 pub mod phong;
+pub mod pipeline;
+pub mod pixelate;
+pub mod taa;
+pub mod vignette;
 use std::sync::{Arc, Mutex};
 
 pub struct ShaderConfig {
-    pub active_model: Arc<Mutex<dyn ShadingModel + Send>>,
-    pub active_post_effects: Vec<Arc<Mutex<dyn PostEffect + Send>>>,
+    pub active_post_effects: Vec<PostEffectEntry>,
     pub selected_effect: Option<PostEffectEnum>,
+    pub view_mode: ViewMode,
+    pub pipeline: pipeline::PipelineSettings,
+}
+
+/// One slot of the post chain. Disabled entries keep their settings but skip their pass.
+#[derive(Clone)]
+pub struct PostEffectEntry {
+    pub effect: Arc<Mutex<dyn PostEffect + Send>>,
+    pub enabled: bool,
+    /// Blend factor between the effect's output (1.0) and its input (0.0).
+    pub mix: f32,
+}
+
+impl PostEffectEntry {
+    pub fn new(effect: Arc<Mutex<dyn PostEffect + Send>>) -> Self {
+        Self {
+            effect,
+            enabled: true,
+            mix: 1.0,
+        }
+    }
+
+    /// A separate entry with copies of this one's settings.
+    pub fn duplicate(&self) -> Self {
+        Self {
+            effect: self.effect.lock().unwrap().duplicate(),
+            ..*self
+        }
+    }
 }
 
 pub trait ShadingModel {
@@ -24,6 +63,41 @@ pub trait ShadingModel {
         eframe::wgpu::Buffer,
     );
     fn to_params(&self) -> &[u8];
+    /// Approximates a PBR material read along with a mesh, if the model has
+    /// parameters that fit.
+    fn set_metallic_roughness(&mut self, _metallic: f32, _roughness: f32) {}
+}
+
+/// How a shading model combines a mesh's vertex colours with its texture, uploaded as
+/// a `u32` in the model's params.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum VertexColorMode {
+    Ignore = 0,
+    Multiply = 1,
+    Replace = 2,
+}
+
+impl VertexColorMode {
+    pub const ALL: [VertexColorMode; 3] = [
+        VertexColorMode::Ignore,
+        VertexColorMode::Multiply,
+        VertexColorMode::Replace,
+    ];
+
+    /// Picks the mode stored in a shading model's params.
+    pub fn build_widget(ui: &mut egui::Ui, mode: &mut u32) -> bool {
+        ui.horizontal(|ui| {
+            ui.label("vertex colours:");
+            let mut changed = false;
+            for option in VertexColorMode::ALL {
+                changed |= ui
+                    .selectable_value(mode, option as u32, format!("{option:?}"))
+                    .changed();
+            }
+            changed
+        })
+        .inner
+    }
 }
 
 pub trait PostEffect {
@@ -34,19 +108,146 @@ pub trait PostEffect {
         target_format: eframe::wgpu::TextureFormat,
     ) -> &egui_wgpu::wgpu::RenderPipeline;
     fn as_enum(&self) -> PostEffectEnum;
+    /// A new effect with the same settings and no GPU resources.
+    fn duplicate(&self) -> Arc<Mutex<dyn PostEffect + Send>>;
+    fn build_widget(&mut self, _ui: &mut egui::Ui) -> bool {
+        false
+    }
+    /// Called each frame the effect runs, before its params are read, with the frame
+    /// number and its place among the enabled effects.
+    fn begin_frame(&mut self, _frame: u32, _position: usize) {}
+    /// Bytes uploaded to the effect's params uniform (group 2), at most `POST_PARAMS_SIZE`.
+    fn to_params(&self) -> &[u8] {
+        &[]
+    }
+    /// Effect-owned resources bound at group 3, for pipelines built with
+    /// `create_post_pipeline_with_layout`.
+    fn get_bind_group(
+        &mut self,
+        _device: &eframe::wgpu::Device,
+        _queue: &eframe::wgpu::Queue,
+    ) -> Option<&eframe::wgpu::BindGroup> {
+        None
+    }
+    /// The copy of an earlier frame the effect reads, for the texture viewer.
+    fn history(&self) -> Option<&eframe::wgpu::Texture> {
+        None
+    }
+    /// Called once the effect's pass is recorded, with the texture it rendered into.
+    /// Effects that need the previous frame copy it out here.
+    fn finish(
+        &mut self,
+        _device: &eframe::wgpu::Device,
+        _encoder: &mut eframe::wgpu::CommandEncoder,
+        _output: &eframe::wgpu::Texture,
+    ) {
+    }
 }
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
 pub enum ShadingModelEnum {
     Phong,
     Flat,
 }
 
+impl ShadingModelEnum {
+    pub const ALL: [ShadingModelEnum; 2] = [ShadingModelEnum::Phong, ShadingModelEnum::Flat];
+
+    pub fn create(self) -> Arc<Mutex<dyn ShadingModel + Send>> {
+        match self {
+            ShadingModelEnum::Phong => Arc::new(Mutex::new(phong::Phong::new())),
+            ShadingModelEnum::Flat => Arc::new(Mutex::new(flat::Flat::new())),
+        }
+    }
+}
+
+/// What the scene pass draws. Anything but `Shaded` bypasses the active shading model.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum ViewMode {
+    Shaded,
+    WorldNormals,
+    ViewNormals,
+    Uvs,
+    LinearDepth,
+    UvChecker,
+    Overdraw,
+    UnlitAlbedo,
+}
+
+impl ViewMode {
+    pub const ALL: [ViewMode; 8] = [
+        ViewMode::Shaded,
+        ViewMode::WorldNormals,
+        ViewMode::ViewNormals,
+        ViewMode::Uvs,
+        ViewMode::LinearDepth,
+        ViewMode::UvChecker,
+        ViewMode::Overdraw,
+        ViewMode::UnlitAlbedo,
+    ];
+}
+
+impl std::fmt::Display for ViewMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub enum PostEffectEnum {
     Negative,
     ChromaticAberration,
     Blur,
+    EdgeDetection,
+    Fog,
+    ColorGrading,
+    Pixelate,
+    Dither,
+    Crt,
+    FilmGrain,
+    Vignette,
+    Taa,
+    MotionBlur,
+}
+
+impl PostEffectEnum {
+    pub const ALL: [PostEffectEnum; 13] = [
+        PostEffectEnum::Negative,
+        PostEffectEnum::ChromaticAberration,
+        PostEffectEnum::Blur,
+        PostEffectEnum::EdgeDetection,
+        PostEffectEnum::Fog,
+        PostEffectEnum::ColorGrading,
+        PostEffectEnum::Pixelate,
+        PostEffectEnum::Dither,
+        PostEffectEnum::Crt,
+        PostEffectEnum::FilmGrain,
+        PostEffectEnum::Vignette,
+        PostEffectEnum::Taa,
+        PostEffectEnum::MotionBlur,
+    ];
+
+    pub fn create(self) -> Arc<Mutex<dyn PostEffect + Send>> {
+        match self {
+            PostEffectEnum::Negative => Arc::new(Mutex::new(negative::Negative::new())),
+            PostEffectEnum::ChromaticAberration => {
+                Arc::new(Mutex::new(chromatic::ChromaticAberration::new()))
+            }
+            PostEffectEnum::Blur => Arc::new(Mutex::new(blur::Blur::new())),
+            PostEffectEnum::EdgeDetection => Arc::new(Mutex::new(edge::EdgeDetection::new())),
+            PostEffectEnum::Fog => Arc::new(Mutex::new(fog::Fog::new())),
+            PostEffectEnum::ColorGrading => {
+                Arc::new(Mutex::new(color_grading::ColorGrading::new()))
+            }
+            PostEffectEnum::Pixelate => Arc::new(Mutex::new(pixelate::Pixelate::new())),
+            PostEffectEnum::Dither => Arc::new(Mutex::new(dither::Dither::new())),
+            PostEffectEnum::Crt => Arc::new(Mutex::new(crt::Crt::new())),
+            PostEffectEnum::FilmGrain => Arc::new(Mutex::new(film_grain::FilmGrain::new())),
+            PostEffectEnum::Vignette => Arc::new(Mutex::new(vignette::Vignette::new())),
+            PostEffectEnum::Taa => Arc::new(Mutex::new(taa::Taa::new())),
+            PostEffectEnum::MotionBlur => Arc::new(Mutex::new(motion_blur::MotionBlur::new())),
+        }
+    }
 }
 
 impl std::fmt::Display for PostEffectEnum {
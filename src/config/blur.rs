@@ -1,4 +1,5 @@
 use crate::{config::PostEffect, renderer};
+use std::sync::{Arc, Mutex};
 
 pub struct Blur {
     pipeline: Option<egui_wgpu::wgpu::RenderPipeline>,
@@ -27,6 +28,10 @@ impl PostEffect for Blur {
     fn as_enum(&self) -> super::PostEffectEnum {
         super::PostEffectEnum::Blur
     }
+
+    fn duplicate(&self) -> Arc<Mutex<dyn PostEffect + Send>> {
+        Arc::new(Mutex::new(Blur::new()))
+    }
 }
 
 impl Blur {
@@ -1,4 +1,5 @@
 use crate::{config::PostEffect, renderer};
+use std::sync::{Arc, Mutex};
 
 pub struct ChromaticAberration {
     pipeline: Option<egui_wgpu::wgpu::RenderPipeline>,
@@ -28,6 +29,10 @@ impl PostEffect for ChromaticAberration {
     fn as_enum(&self) -> super::PostEffectEnum {
         super::PostEffectEnum::ChromaticAberration
     }
+
+    fn duplicate(&self) -> Arc<Mutex<dyn PostEffect + Send>> {
+        Arc::new(Mutex::new(ChromaticAberration::new()))
+    }
 }
 
 impl ChromaticAberration {
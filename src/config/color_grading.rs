@@ -0,0 +1,486 @@
+use crate::{config::PostEffect, renderer};
+use eframe::egui_wgpu::wgpu;
+
+use egui_file::FileDialog;
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ColorGradingParams {
+    lift: [f32; 4],
+    gamma: [f32; 4],
+    gain: [f32; 4],
+    domain_min: [f32; 4],
+    domain_max: [f32; 4],
+    strength: f32,
+    saturation: f32,
+    contrast: f32,
+    use_lut: u32,
+}
+
+/// A 3D lookup table read from an Adobe/Resolve `.cube` file.
+pub struct CubeLut {
+    pub size: u32,
+    pub domain_min: [f32; 3],
+    pub domain_max: [f32; 3],
+    /// Output colours with red varying fastest, then green, then blue.
+    pub data: Vec<[f32; 3]>,
+}
+
+impl CubeLut {
+    pub fn load(path: &Path) -> Result<CubeLut, String> {
+        let src = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        CubeLut::parse(&src)
+    }
+
+    pub fn parse(src: &str) -> Result<CubeLut, String> {
+        let mut size = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut data = Vec::new();
+
+        for (line_number, line) in src.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |msg: &str| format!("line {}: {msg}", line_number + 1);
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap();
+            let mut floats = || -> Result<Vec<f32>, String> {
+                words
+                    .by_ref()
+                    .map(|w| w.parse::<f32>().map_err(|_| error("expected a number")))
+                    .collect()
+            };
+            match keyword {
+                "TITLE" => {}
+                "LUT_1D_SIZE" => return Err(error("1D LUTs are not supported")),
+                "LUT_3D_SIZE" => {
+                    let n = words
+                        .next()
+                        .and_then(|w| w.parse::<u32>().ok())
+                        .filter(|n| (2..=256).contains(n))
+                        .ok_or_else(|| error("LUT_3D_SIZE must be between 2 and 256"))?;
+                    size = Some(n);
+                }
+                "DOMAIN_MIN" | "DOMAIN_MAX" => {
+                    let values: [f32; 3] = floats()?
+                        .try_into()
+                        .map_err(|_| error("expected three numbers"))?;
+                    if keyword == "DOMAIN_MIN" {
+                        domain_min = values;
+                    } else {
+                        domain_max = values;
+                    }
+                }
+                "LUT_3D_INPUT_RANGE" => {
+                    let [min, max]: [f32; 2] = floats()?
+                        .try_into()
+                        .map_err(|_| error("expected two numbers"))?;
+                    domain_min = [min; 3];
+                    domain_max = [max; 3];
+                }
+                _ if keyword.starts_with(|c: char| c.is_ascii_alphabetic()) => {}
+                _ => {
+                    let rgb: [f32; 3] = std::iter::once(keyword)
+                        .chain(words)
+                        .map(|w| w.parse::<f32>().map_err(|_| error("expected a number")))
+                        .collect::<Result<Vec<_>, _>>()?
+                        .try_into()
+                        .map_err(|_| error("expected three numbers"))?;
+                    data.push(rgb);
+                }
+            }
+        }
+
+        let size = size.ok_or("missing LUT_3D_SIZE")?;
+        if data.len() != (size * size * size) as usize {
+            return Err(format!(
+                "expected {} entries for a size {size} LUT, found {}",
+                size * size * size,
+                data.len()
+            ));
+        }
+        Ok(CubeLut {
+            size,
+            domain_min,
+            domain_max,
+            data,
+        })
+    }
+
+    fn identity(size: u32) -> CubeLut {
+        let max = (size - 1) as f32;
+        let data = (0..size * size * size)
+            .map(|i| {
+                [
+                    (i % size) as f32 / max,
+                    (i / size % size) as f32 / max,
+                    (i / (size * size)) as f32 / max,
+                ]
+            })
+            .collect();
+        CubeLut {
+            size,
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+            data,
+        }
+    }
+}
+
+fn lut_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D3,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+        label: Some("lut_bind_group_layout"),
+    })
+}
+
+pub struct ColorGrading {
+    pipeline: Option<wgpu::RenderPipeline>,
+    bind_group: Option<wgpu::BindGroup>,
+    params: ColorGradingParams,
+    lut_file: Option<PathBuf>,
+    open_file_dialog: Option<FileDialog>,
+    lut: Option<Arc<CubeLut>>,
+    lut_error: Option<String>,
+}
+
+impl PostEffect for ColorGrading {
+    fn get_source(&self) -> String {
+        let _path: std::path::PathBuf = ["post", "color_grading.wgsl"].iter().collect();
+        include_str!("post/color_grading.wgsl").into()
+    }
+
+    fn get_pipeline(
+        &mut self,
+        device: &eframe::wgpu::Device,
+        target_format: eframe::wgpu::TextureFormat,
+    ) -> &egui_wgpu::wgpu::RenderPipeline {
+        if self.pipeline.is_none() {
+            self.pipeline = Some(renderer::create_post_pipeline_with_layout(
+                device,
+                target_format,
+                self.get_source(),
+                Some(&lut_bind_group_layout(device)),
+            ));
+        }
+        self.pipeline.as_ref().unwrap()
+    }
+
+    fn as_enum(&self) -> super::PostEffectEnum {
+        super::PostEffectEnum::ColorGrading
+    }
+
+    fn duplicate(&self) -> Arc<Mutex<dyn PostEffect + Send>> {
+        Arc::new(Mutex::new(ColorGrading {
+            pipeline: None,
+            bind_group: None,
+            params: self.params,
+            lut_file: self.lut_file.clone(),
+            open_file_dialog: None,
+            lut: self.lut.clone(),
+            lut_error: None,
+        }))
+    }
+
+    fn build_widget(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.vertical(|ui| {
+            let mut should_update = false;
+            ui.style_mut().spacing.slider_width = ui.available_width();
+
+            ui.horizontal(|ui| {
+                if ui.button("Load LUT").clicked() {
+                    // Show only files with the extension "cube", in any case.
+                    let filter = Box::new(|path: &Path| -> bool {
+                        path.extension()
+                            .is_some_and(|ext| ext.eq_ignore_ascii_case("cube"))
+                    });
+                    let mut dialog =
+                        FileDialog::open_file(self.lut_file.clone()).show_files_filter(filter);
+                    dialog.open();
+                    self.open_file_dialog = Some(dialog);
+                }
+                if self.params.use_lut != 0 && ui.button("Clear LUT").clicked() {
+                    self.params.use_lut = 0;
+                    should_update = true;
+                }
+            });
+
+            if let Some(dialog) = &mut self.open_file_dialog
+                && dialog.show(ui.ctx()).selected()
+                && let Some(file) = dialog.path()
+            {
+                self.lut_file = Some(file.to_path_buf());
+                match CubeLut::load(file) {
+                    Ok(lut) => {
+                        let [r, g, b] = lut.domain_min;
+                        self.params.domain_min = [r, g, b, 0.0];
+                        let [r, g, b] = lut.domain_max;
+                        self.params.domain_max = [r, g, b, 1.0];
+                        self.lut = Some(Arc::new(lut));
+                        self.bind_group = None;
+                        self.lut_error = None;
+                        self.params.use_lut = 1;
+                    }
+                    Err(e) => self.lut_error = Some(e),
+                }
+                should_update = true;
+            }
+
+            if let Some(error) = &self.lut_error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+
+            if self.params.use_lut != 0 {
+                if let Some(file) = self.lut_file.as_ref().and_then(|f| f.file_name()) {
+                    ui.label(format!("LUT: {}", file.to_string_lossy()));
+                }
+                ui.label(format!("strength: {}", self.params.strength));
+                should_update |= ui
+                    .add(egui::Slider::new(&mut self.params.strength, 0.0..=1.0))
+                    .drag_stopped();
+            } else {
+                for (name, value, range) in [
+                    ("lift", &mut self.params.lift, -0.5..=0.5),
+                    ("gamma", &mut self.params.gamma, 0.2..=3.0),
+                    ("gain", &mut self.params.gain, 0.0..=2.0),
+                ] {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{name}:"));
+                        for channel in &mut value[..3] {
+                            should_update |= ui
+                                .add(
+                                    egui::DragValue::new(channel)
+                                        .speed(0.01)
+                                        .range(range.clone()),
+                                )
+                                .changed();
+                        }
+                    });
+                }
+
+                ui.label(format!("saturation: {}", self.params.saturation));
+                should_update |= ui
+                    .add(egui::Slider::new(&mut self.params.saturation, 0.0..=2.0))
+                    .drag_stopped();
+
+                ui.label(format!("contrast: {}", self.params.contrast));
+                should_update |= ui
+                    .add(egui::Slider::new(&mut self.params.contrast, 0.0..=2.0))
+                    .drag_stopped();
+            }
+
+            should_update
+        })
+        .inner
+    }
+
+    fn to_params(&self) -> &[u8] {
+        bytemuck::bytes_of(&self.params)
+    }
+
+    fn get_bind_group(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Option<&wgpu::BindGroup> {
+        if self.bind_group.is_none() {
+            let bind_group = match &self.lut {
+                Some(lut) => Self::upload_lut(device, queue, lut),
+                None => Self::upload_lut(device, queue, &CubeLut::identity(2)),
+            };
+            self.bind_group = Some(bind_group);
+        }
+        self.bind_group.as_ref()
+    }
+}
+
+impl ColorGrading {
+    pub fn new() -> ColorGrading {
+        ColorGrading {
+            pipeline: None,
+            bind_group: None,
+            params: ColorGradingParams {
+                lift: [0.0; 4],
+                gamma: [1.0; 4],
+                gain: [1.0; 4],
+                domain_min: [0.0; 4],
+                domain_max: [1.0; 4],
+                strength: 1.0,
+                saturation: 1.0,
+                contrast: 1.0,
+                use_lut: 0,
+            },
+            lut_file: None,
+            open_file_dialog: None,
+            lut: None,
+            lut_error: None,
+        }
+    }
+
+    fn upload_lut(device: &wgpu::Device, queue: &wgpu::Queue, lut: &CubeLut) -> wgpu::BindGroup {
+        let size = wgpu::Extent3d {
+            width: lut.size,
+            height: lut.size,
+            depth_or_array_layers: lut.size,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("color_grading_lut"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D3,
+            format: wgpu::TextureFormat::Rgba16Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let texels: Vec<u16> = lut
+            .data
+            .iter()
+            .flat_map(|&[r, g, b]| [r, g, b, 1.0])
+            .map(|c| half::f16::from_f32(c).to_bits())
+            .collect();
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            bytemuck::cast_slice(&texels),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(8 * lut.size),
+                rows_per_image: Some(lut.size),
+            },
+            size,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &lut_bind_group_layout(device),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("color_grading_lut"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CubeLut;
+
+    /// The entries of an identity LUT of `size`, one per line.
+    fn entries(size: u32) -> String {
+        CubeLut::identity(size)
+            .data
+            .iter()
+            .map(|[r, g, b]| format!("{r} {g} {b}\n"))
+            .collect()
+    }
+
+    fn parse_error(src: &str) -> String {
+        match CubeLut::parse(src) {
+            Ok(_) => panic!("parsed an invalid LUT"),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn parses_a_size_2_lut() {
+        let src = format!(
+            "# Comment\nTITLE \"Identity\"\nLUT_3D_SIZE 2\n\n{}",
+            entries(2)
+        );
+        let lut = CubeLut::parse(&src).unwrap();
+        assert_eq!(lut.size, 2);
+        assert_eq!(lut.domain_min, [0.0; 3]);
+        assert_eq!(lut.domain_max, [1.0; 3]);
+        assert_eq!(lut.data.len(), 8);
+        // Red varies fastest.
+        assert_eq!(lut.data[1], [1.0, 0.0, 0.0]);
+        assert_eq!(lut.data[2], [0.0, 1.0, 0.0]);
+        assert_eq!(lut.data[4], [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn rejects_a_wrong_entry_count() {
+        let src = format!("LUT_3D_SIZE 2\n{}", entries(2));
+        let short = src.lines().take(8).collect::<Vec<_>>().join("\n");
+        assert_eq!(
+            parse_error(&short),
+            "expected 8 entries for a size 2 LUT, found 7"
+        );
+        let long = format!("{src}0 0 0\n");
+        assert!(parse_error(&long).contains("found 9"));
+    }
+
+    #[test]
+    fn rejects_an_out_of_range_size() {
+        for size in ["1", "257", "two"] {
+            let error = parse_error(&format!("LUT_3D_SIZE {size}\n"));
+            assert_eq!(error, "line 1: LUT_3D_SIZE must be between 2 and 256");
+        }
+        assert!(parse_error("LUT_1D_SIZE 16\n").contains("not supported"));
+    }
+
+    #[test]
+    fn reads_a_custom_domain() {
+        let src = format!(
+            "LUT_3D_SIZE 2\nDOMAIN_MIN -0.5 0 0.25\nDOMAIN_MAX 2 1 4\n{}",
+            entries(2)
+        );
+        let lut = CubeLut::parse(&src).unwrap();
+        assert_eq!(lut.domain_min, [-0.5, 0.0, 0.25]);
+        assert_eq!(lut.domain_max, [2.0, 1.0, 4.0]);
+
+        let src = format!("LUT_3D_INPUT_RANGE 0 4\nLUT_3D_SIZE 2\n{}", entries(2));
+        let lut = CubeLut::parse(&src).unwrap();
+        assert_eq!(lut.domain_min, [0.0; 3]);
+        assert_eq!(lut.domain_max, [4.0; 3]);
+
+        assert_eq!(
+            parse_error("DOMAIN_MIN 0 0\n"),
+            "line 1: expected three numbers"
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{config::PostEffect, renderer};
+use std::sync::{Arc, Mutex};
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct CrtParams {
+    curvature: f32,
+    scanline_intensity: f32,
+    scanline_count: f32,
+    mask_intensity: f32,
+    flicker: f32,
+    _padding: [u32; 3],
+}
+
+pub struct Crt {
+    pipeline: Option<egui_wgpu::wgpu::RenderPipeline>,
+    params: CrtParams,
+}
+
+impl PostEffect for Crt {
+    fn get_source(&self) -> String {
+        let _path: std::path::PathBuf = ["post", "crt.wgsl"].iter().collect();
+        include_str!("post/crt.wgsl").into()
+    }
+
+    fn get_pipeline(
+        &mut self,
+        device: &eframe::wgpu::Device,
+        target_format: eframe::wgpu::TextureFormat,
+    ) -> &egui_wgpu::wgpu::RenderPipeline {
+        if self.pipeline.is_none() {
+            self.pipeline = Some(renderer::create_post_pipeline(
+                device,
+                target_format,
+                self.get_source(),
+            ));
+        }
+        self.pipeline.as_ref().unwrap()
+    }
+
+    fn as_enum(&self) -> super::PostEffectEnum {
+        super::PostEffectEnum::Crt
+    }
+
+    fn duplicate(&self) -> Arc<Mutex<dyn PostEffect + Send>> {
+        Arc::new(Mutex::new(Crt {
+            pipeline: None,
+            params: self.params,
+        }))
+    }
+
+    fn build_widget(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.vertical(|ui| {
+            let mut should_update = false;
+            ui.style_mut().spacing.slider_width = ui.available_width();
+
+            ui.label(format!("curvature: {}", self.params.curvature));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.curvature, 0.0..=0.5))
+                .drag_stopped();
+
+            ui.label(format!(
+                "scanline intensity: {}",
+                self.params.scanline_intensity
+            ));
+            should_update |= ui
+                .add(egui::Slider::new(
+                    &mut self.params.scanline_intensity,
+                    0.0..=1.0,
+                ))
+                .drag_stopped();
+
+            ui.label(format!("scanline count: {}", self.params.scanline_count));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.scanline_count, 50.0..=1000.0).step_by(1.0))
+                .drag_stopped();
+
+            ui.label(format!(
+                "shadow mask intensity: {}",
+                self.params.mask_intensity
+            ));
+            should_update |= ui
+                .add(egui::Slider::new(
+                    &mut self.params.mask_intensity,
+                    0.0..=1.0,
+                ))
+                .drag_stopped();
+
+            ui.label(format!("flicker: {}", self.params.flicker));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.flicker, 0.0..=0.2))
+                .drag_stopped();
+
+            should_update
+        })
+        .inner
+    }
+
+    fn to_params(&self) -> &[u8] {
+        bytemuck::bytes_of(&self.params)
+    }
+}
+
+impl Crt {
+    pub fn new() -> Crt {
+        Crt {
+            pipeline: None,
+            params: CrtParams {
+                curvature: 0.1,
+                scanline_intensity: 0.5,
+                scanline_count: 240.0,
+                mask_intensity: 0.3,
+                flicker: 0.03,
+                _padding: [0; 3],
+            },
+        }
+    }
+}
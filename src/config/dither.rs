@@ -0,0 +1,162 @@
+use crate::{config::PostEffect, renderer};
+use std::sync::{Arc, Mutex};
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum DitherPalette {
+    Quantize,
+    Monochrome,
+    GameBoy,
+    Cga,
+    Sepia,
+}
+
+impl DitherPalette {
+    const ALL: [DitherPalette; 5] = [
+        DitherPalette::Quantize,
+        DitherPalette::Monochrome,
+        DitherPalette::GameBoy,
+        DitherPalette::Cga,
+        DitherPalette::Sepia,
+    ];
+
+    fn colors(&self) -> &'static [[f32; 3]] {
+        match self {
+            DitherPalette::Quantize => &[],
+            DitherPalette::Monochrome => &[[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]],
+            DitherPalette::GameBoy => &[
+                [0.059, 0.220, 0.059],
+                [0.188, 0.384, 0.188],
+                [0.545, 0.675, 0.059],
+                [0.608, 0.737, 0.059],
+            ],
+            DitherPalette::Cga => &[
+                [0.0, 0.0, 0.0],
+                [0.333, 1.0, 1.0],
+                [1.0, 0.333, 1.0],
+                [1.0, 1.0, 1.0],
+            ],
+            DitherPalette::Sepia => &[
+                [0.098, 0.063, 0.035],
+                [0.384, 0.259, 0.157],
+                [0.706, 0.549, 0.376],
+                [0.961, 0.902, 0.784],
+            ],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct DitherParams {
+    palette: [[f32; 4]; 8],
+    palette_size: u32,
+    levels: f32,
+    spread: f32,
+    pixel_size: f32,
+}
+
+pub struct Dither {
+    pipeline: Option<egui_wgpu::wgpu::RenderPipeline>,
+    params: DitherParams,
+    palette: DitherPalette,
+}
+
+impl PostEffect for Dither {
+    fn get_source(&self) -> String {
+        let _path: std::path::PathBuf = ["post", "dither.wgsl"].iter().collect();
+        include_str!("post/dither.wgsl").into()
+    }
+
+    fn get_pipeline(
+        &mut self,
+        device: &eframe::wgpu::Device,
+        target_format: eframe::wgpu::TextureFormat,
+    ) -> &egui_wgpu::wgpu::RenderPipeline {
+        if self.pipeline.is_none() {
+            self.pipeline = Some(renderer::create_post_pipeline(
+                device,
+                target_format,
+                self.get_source(),
+            ));
+        }
+        self.pipeline.as_ref().unwrap()
+    }
+
+    fn as_enum(&self) -> super::PostEffectEnum {
+        super::PostEffectEnum::Dither
+    }
+
+    fn duplicate(&self) -> Arc<Mutex<dyn PostEffect + Send>> {
+        Arc::new(Mutex::new(Dither {
+            pipeline: None,
+            params: self.params,
+            palette: self.palette,
+        }))
+    }
+
+    fn build_widget(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.vertical(|ui| {
+            let mut should_update = false;
+            ui.style_mut().spacing.slider_width = ui.available_width();
+
+            egui::ComboBox::from_label("palette")
+                .selected_text(format!("{:?}", self.palette))
+                .show_ui(ui, |ui| {
+                    for option in DitherPalette::ALL {
+                        should_update |= ui
+                            .selectable_value(&mut self.palette, option, format!("{option:?}"))
+                            .changed();
+                    }
+                });
+            self.set_palette(self.palette);
+
+            if self.palette == DitherPalette::Quantize {
+                ui.label(format!("levels per channel: {}", self.params.levels));
+                should_update |= ui
+                    .add(egui::Slider::new(&mut self.params.levels, 2.0..=16.0).step_by(1.0))
+                    .drag_stopped();
+            }
+
+            ui.label(format!("spread: {}", self.params.spread));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.spread, 0.0..=1.0))
+                .drag_stopped();
+
+            ui.label(format!("pixel size: {}", self.params.pixel_size));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.pixel_size, 1.0..=16.0).step_by(1.0))
+                .drag_stopped();
+
+            should_update
+        })
+        .inner
+    }
+
+    fn to_params(&self) -> &[u8] {
+        bytemuck::bytes_of(&self.params)
+    }
+}
+
+impl Dither {
+    pub fn new() -> Dither {
+        Dither {
+            pipeline: None,
+            palette: DitherPalette::Quantize,
+            params: DitherParams {
+                palette: [[0.0; 4]; 8],
+                palette_size: 0,
+                levels: 4.0,
+                spread: 1.0,
+                pixel_size: 1.0,
+            },
+        }
+    }
+
+    fn set_palette(&mut self, palette: DitherPalette) {
+        let colors = palette.colors();
+        for (slot, [r, g, b]) in self.params.palette.iter_mut().zip(colors) {
+            *slot = [*r, *g, *b, 1.0];
+        }
+        self.params.palette_size = colors.len() as u32;
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{config::PostEffect, renderer};
+use std::sync::{Arc, Mutex};
+
+const SOURCE_DEPTH: u32 = 1;
+const SOURCE_NORMAL: u32 = 2;
+const SOURCE_COLOR: u32 = 4;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct EdgeParams {
+    color: [f32; 4],
+    thickness: f32,
+    depth_threshold: f32,
+    normal_threshold: f32,
+    color_threshold: f32,
+    kernel: u32,
+    sources: u32,
+    _padding: [u32; 2],
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum EdgeOperator {
+    Sobel = 0,
+    Roberts = 1,
+}
+
+pub struct EdgeDetection {
+    pipeline: Option<egui_wgpu::wgpu::RenderPipeline>,
+    params: EdgeParams,
+}
+
+impl PostEffect for EdgeDetection {
+    fn get_source(&self) -> String {
+        let _path: std::path::PathBuf = ["post", "edge.wgsl"].iter().collect();
+        include_str!("post/edge.wgsl").into()
+    }
+
+    fn get_pipeline(
+        &mut self,
+        device: &eframe::wgpu::Device,
+        target_format: eframe::wgpu::TextureFormat,
+    ) -> &egui_wgpu::wgpu::RenderPipeline {
+        if self.pipeline.is_none() {
+            self.pipeline = Some(renderer::create_post_pipeline(
+                device,
+                target_format,
+                self.get_source(),
+            ));
+        }
+        self.pipeline.as_ref().unwrap()
+    }
+
+    fn as_enum(&self) -> super::PostEffectEnum {
+        super::PostEffectEnum::EdgeDetection
+    }
+
+    fn duplicate(&self) -> Arc<Mutex<dyn PostEffect + Send>> {
+        Arc::new(Mutex::new(EdgeDetection {
+            pipeline: None,
+            params: self.params,
+        }))
+    }
+
+    fn build_widget(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.vertical(|ui| {
+            let mut should_update = false;
+            ui.style_mut().spacing.slider_width = ui.available_width();
+
+            ui.horizontal(|ui| {
+                ui.label("line colour:");
+                should_update |= ui
+                    .color_edit_button_rgba_unmultiplied(&mut self.params.color)
+                    .changed();
+            });
+
+            let mut operator = if self.params.kernel == EdgeOperator::Roberts as u32 {
+                EdgeOperator::Roberts
+            } else {
+                EdgeOperator::Sobel
+            };
+            ui.horizontal(|ui| {
+                ui.label("operator:");
+                should_update |= ui
+                    .selectable_value(&mut operator, EdgeOperator::Sobel, "Sobel")
+                    .changed();
+                should_update |= ui
+                    .selectable_value(&mut operator, EdgeOperator::Roberts, "Roberts")
+                    .changed();
+            });
+            self.params.kernel = operator as u32;
+
+            ui.label(format!("thickness: {}", self.params.thickness));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.thickness, 1.0..=8.0).step_by(1.0))
+                .drag_stopped();
+
+            for (source, name, threshold, range) in [
+                (
+                    SOURCE_DEPTH,
+                    "depth",
+                    &mut self.params.depth_threshold,
+                    0.0..=2.0,
+                ),
+                (
+                    SOURCE_NORMAL,
+                    "normal",
+                    &mut self.params.normal_threshold,
+                    0.0..=4.0,
+                ),
+                (
+                    SOURCE_COLOR,
+                    "colour",
+                    &mut self.params.color_threshold,
+                    0.0..=2.0,
+                ),
+            ] {
+                let mut enabled = self.params.sources & source != 0;
+                if ui.checkbox(&mut enabled, format!("{name} edges")).changed() {
+                    self.params.sources ^= source;
+                    should_update = true;
+                }
+                if enabled {
+                    ui.label(format!("{name} threshold: {threshold}"));
+                    should_update |= ui.add(egui::Slider::new(threshold, range)).drag_stopped();
+                }
+            }
+
+            should_update
+        })
+        .inner
+    }
+
+    fn to_params(&self) -> &[u8] {
+        bytemuck::bytes_of(&self.params)
+    }
+}
+
+impl EdgeDetection {
+    pub fn new() -> EdgeDetection {
+        EdgeDetection {
+            pipeline: None,
+            params: EdgeParams {
+                color: [0.0, 0.0, 0.0, 1.0],
+                thickness: 1.0,
+                depth_threshold: 0.5,
+                normal_threshold: 1.0,
+                color_threshold: 0.5,
+                kernel: EdgeOperator::Sobel as u32,
+                sources: SOURCE_DEPTH | SOURCE_NORMAL,
+                _padding: [0; 2],
+            },
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{config::PostEffect, renderer};
+use std::sync::{Arc, Mutex};
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct FilmGrainParams {
+    intensity: f32,
+    grain_size: f32,
+    luminance_response: f32,
+    frame_rate: f32,
+    colored: u32,
+    _padding: [u32; 3],
+}
+
+pub struct FilmGrain {
+    pipeline: Option<egui_wgpu::wgpu::RenderPipeline>,
+    params: FilmGrainParams,
+}
+
+impl PostEffect for FilmGrain {
+    fn get_source(&self) -> String {
+        let _path: std::path::PathBuf = ["post", "film_grain.wgsl"].iter().collect();
+        include_str!("post/film_grain.wgsl").into()
+    }
+
+    fn get_pipeline(
+        &mut self,
+        device: &eframe::wgpu::Device,
+        target_format: eframe::wgpu::TextureFormat,
+    ) -> &egui_wgpu::wgpu::RenderPipeline {
+        if self.pipeline.is_none() {
+            self.pipeline = Some(renderer::create_post_pipeline(
+                device,
+                target_format,
+                self.get_source(),
+            ));
+        }
+        self.pipeline.as_ref().unwrap()
+    }
+
+    fn as_enum(&self) -> super::PostEffectEnum {
+        super::PostEffectEnum::FilmGrain
+    }
+
+    fn duplicate(&self) -> Arc<Mutex<dyn PostEffect + Send>> {
+        Arc::new(Mutex::new(FilmGrain {
+            pipeline: None,
+            params: self.params,
+        }))
+    }
+
+    fn build_widget(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.vertical(|ui| {
+            let mut should_update = false;
+            ui.style_mut().spacing.slider_width = ui.available_width();
+
+            ui.label(format!("intensity: {}", self.params.intensity));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.intensity, 0.0..=1.0))
+                .drag_stopped();
+
+            ui.label(format!("grain size: {}", self.params.grain_size));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.grain_size, 1.0..=8.0))
+                .drag_stopped();
+
+            ui.label(format!(
+                "luminance response: {}",
+                self.params.luminance_response
+            ));
+            should_update |= ui
+                .add(egui::Slider::new(
+                    &mut self.params.luminance_response,
+                    0.0..=1.0,
+                ))
+                .drag_stopped();
+
+            ui.label(format!("grain frame rate: {}", self.params.frame_rate));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.frame_rate, 1.0..=60.0).step_by(1.0))
+                .drag_stopped();
+
+            let mut colored = self.params.colored != 0;
+            if ui.checkbox(&mut colored, "coloured grain").changed() {
+                self.params.colored = colored as u32;
+                should_update = true;
+            }
+
+            should_update
+        })
+        .inner
+    }
+
+    fn to_params(&self) -> &[u8] {
+        bytemuck::bytes_of(&self.params)
+    }
+}
+
+impl FilmGrain {
+    pub fn new() -> FilmGrain {
+        FilmGrain {
+            pipeline: None,
+            params: FilmGrainParams {
+                intensity: 0.15,
+                grain_size: 1.5,
+                luminance_response: 0.5,
+                frame_rate: 24.0,
+                colored: 0,
+                _padding: [0; 3],
+            },
+        }
+    }
+}
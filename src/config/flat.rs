@@ -1,4 +1,4 @@
-use crate::config::{ShadingModel, ShadingModelEnum};
+use crate::config::{ShadingModel, ShadingModelEnum, VertexColorMode};
 use eframe::egui_wgpu::wgpu;
 
 #[repr(C)]
@@ -8,6 +8,8 @@ pub struct Flat {
     kd: f32,
     ks: f32,
     alph: f32,
+    vertex_color: u32,
+    _padding: [u32; 3],
 }
 
 impl Flat {
@@ -17,6 +19,8 @@ impl Flat {
             kd: 0.4,
             ks: 0.4,
             alph: 4.0,
+            vertex_color: VertexColorMode::Multiply as u32,
+            _padding: [0; 3],
         }
     }
 }
@@ -47,6 +51,8 @@ impl ShadingModel for Flat {
                 .add(egui::Slider::new(&mut self.alph, 0.0..=100.0))
                 .drag_stopped();
 
+            should_update |= VertexColorMode::build_widget(ui, &mut self.vertex_color);
+
             should_update
         })
         .inner
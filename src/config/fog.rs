@@ -0,0 +1,192 @@
+use crate::{config::PostEffect, renderer};
+use std::sync::{Arc, Mutex};
+
+const FLAG_HEIGHT: u32 = 1;
+const FLAG_BACKGROUND: u32 = 2;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct FogParams {
+    color: [f32; 4],
+    mode: u32,
+    density: f32,
+    start: f32,
+    end: f32,
+    height_density: f32,
+    height_falloff: f32,
+    height_base: f32,
+    flags: u32,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum FogMode {
+    Off = 0,
+    Linear = 1,
+    Exponential = 2,
+    ExponentialSquared = 3,
+}
+
+impl FogMode {
+    const ALL: [FogMode; 4] = [
+        FogMode::Off,
+        FogMode::Linear,
+        FogMode::Exponential,
+        FogMode::ExponentialSquared,
+    ];
+}
+
+pub struct Fog {
+    pipeline: Option<egui_wgpu::wgpu::RenderPipeline>,
+    params: FogParams,
+}
+
+impl PostEffect for Fog {
+    fn get_source(&self) -> String {
+        let _path: std::path::PathBuf = ["post", "fog.wgsl"].iter().collect();
+        include_str!("post/fog.wgsl").into()
+    }
+
+    fn get_pipeline(
+        &mut self,
+        device: &eframe::wgpu::Device,
+        target_format: eframe::wgpu::TextureFormat,
+    ) -> &egui_wgpu::wgpu::RenderPipeline {
+        if self.pipeline.is_none() {
+            self.pipeline = Some(renderer::create_post_pipeline(
+                device,
+                target_format,
+                self.get_source(),
+            ));
+        }
+        self.pipeline.as_ref().unwrap()
+    }
+
+    fn as_enum(&self) -> super::PostEffectEnum {
+        super::PostEffectEnum::Fog
+    }
+
+    fn duplicate(&self) -> Arc<Mutex<dyn PostEffect + Send>> {
+        Arc::new(Mutex::new(Fog {
+            pipeline: None,
+            params: self.params,
+        }))
+    }
+
+    fn build_widget(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.vertical(|ui| {
+            let mut should_update = false;
+            ui.style_mut().spacing.slider_width = ui.available_width();
+
+            ui.horizontal(|ui| {
+                ui.label("fog colour:");
+                should_update |= ui
+                    .color_edit_button_rgba_unmultiplied(&mut self.params.color)
+                    .changed();
+            });
+
+            let mut mode = FogMode::ALL
+                .into_iter()
+                .find(|mode| *mode as u32 == self.params.mode)
+                .unwrap_or(FogMode::Off);
+            egui::ComboBox::from_label("distance fog")
+                .selected_text(format!("{mode:?}"))
+                .show_ui(ui, |ui| {
+                    for option in FogMode::ALL {
+                        should_update |= ui
+                            .selectable_value(&mut mode, option, format!("{option:?}"))
+                            .changed();
+                    }
+                });
+            self.params.mode = mode as u32;
+
+            match mode {
+                FogMode::Off => {}
+                FogMode::Linear => {
+                    ui.label(format!("start: {}", self.params.start));
+                    should_update |= ui
+                        .add(egui::Slider::new(&mut self.params.start, 0.0..=100.0))
+                        .drag_stopped();
+
+                    ui.label(format!("end: {}", self.params.end));
+                    should_update |= ui
+                        .add(egui::Slider::new(&mut self.params.end, 0.0..=100.0))
+                        .drag_stopped();
+                }
+                FogMode::Exponential | FogMode::ExponentialSquared => {
+                    ui.label(format!("start: {}", self.params.start));
+                    should_update |= ui
+                        .add(egui::Slider::new(&mut self.params.start, 0.0..=100.0))
+                        .drag_stopped();
+
+                    ui.label(format!("density: {}", self.params.density));
+                    should_update |= ui
+                        .add(egui::Slider::new(&mut self.params.density, 0.0..=2.0))
+                        .drag_stopped();
+                }
+            }
+
+            let mut height = self.params.flags & FLAG_HEIGHT != 0;
+            if ui.checkbox(&mut height, "height fog").changed() {
+                self.params.flags ^= FLAG_HEIGHT;
+                should_update = true;
+            }
+            if height {
+                ui.label(format!("height density: {}", self.params.height_density));
+                should_update |= ui
+                    .add(egui::Slider::new(
+                        &mut self.params.height_density,
+                        0.0..=2.0,
+                    ))
+                    .drag_stopped();
+
+                ui.label(format!("height falloff: {}", self.params.height_falloff));
+                should_update |= ui
+                    .add(egui::Slider::new(
+                        &mut self.params.height_falloff,
+                        0.01..=5.0,
+                    ))
+                    .drag_stopped();
+
+                ui.label(format!("base height: {}", self.params.height_base));
+                should_update |= ui
+                    .add(egui::Slider::new(
+                        &mut self.params.height_base,
+                        -10.0..=10.0,
+                    ))
+                    .drag_stopped();
+            }
+
+            let mut background = self.params.flags & FLAG_BACKGROUND != 0;
+            if ui.checkbox(&mut background, "fog background").changed() {
+                self.params.flags ^= FLAG_BACKGROUND;
+                should_update = true;
+            }
+
+            should_update
+        })
+        .inner
+    }
+
+    fn to_params(&self) -> &[u8] {
+        bytemuck::bytes_of(&self.params)
+    }
+}
+
+impl Fog {
+    pub fn new() -> Fog {
+        Fog {
+            pipeline: None,
+            params: FogParams {
+                color: [0.7, 0.75, 0.8, 1.0],
+                mode: FogMode::Exponential as u32,
+                density: 0.15,
+                start: 0.0,
+                end: 20.0,
+                height_density: 0.3,
+                height_falloff: 1.0,
+                height_base: 0.0,
+                flags: FLAG_BACKGROUND,
+            },
+        }
+    }
+}
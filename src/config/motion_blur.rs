@@ -0,0 +1,96 @@
+use crate::{config::PostEffect, renderer};
+use std::sync::{Arc, Mutex};
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct MotionBlurParams {
+    shutter_angle: f32,
+    samples: u32,
+    max_length: f32,
+    _padding: u32,
+}
+
+/// Blurs along the per-pixel velocity caused by camera movement between frames.
+pub struct MotionBlur {
+    pipeline: Option<egui_wgpu::wgpu::RenderPipeline>,
+    params: MotionBlurParams,
+}
+
+impl PostEffect for MotionBlur {
+    fn get_source(&self) -> String {
+        let _path: std::path::PathBuf = ["post", "motion_blur.wgsl"].iter().collect();
+        include_str!("post/motion_blur.wgsl").into()
+    }
+
+    fn get_pipeline(
+        &mut self,
+        device: &eframe::wgpu::Device,
+        target_format: eframe::wgpu::TextureFormat,
+    ) -> &egui_wgpu::wgpu::RenderPipeline {
+        if self.pipeline.is_none() {
+            self.pipeline = Some(renderer::create_post_pipeline(
+                device,
+                target_format,
+                self.get_source(),
+            ));
+        }
+        self.pipeline.as_ref().unwrap()
+    }
+
+    fn as_enum(&self) -> super::PostEffectEnum {
+        super::PostEffectEnum::MotionBlur
+    }
+
+    fn duplicate(&self) -> Arc<Mutex<dyn PostEffect + Send>> {
+        Arc::new(Mutex::new(MotionBlur {
+            pipeline: None,
+            params: self.params,
+        }))
+    }
+
+    fn build_widget(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.vertical(|ui| {
+            let mut should_update = false;
+            ui.style_mut().spacing.slider_width = ui.available_width();
+
+            ui.label(format!("shutter angle: {}°", self.params.shutter_angle));
+            should_update |= ui
+                .add(egui::Slider::new(
+                    &mut self.params.shutter_angle,
+                    0.0..=360.0,
+                ))
+                .drag_stopped();
+
+            ui.label(format!("samples: {}", self.params.samples));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.samples, 2..=64))
+                .drag_stopped();
+
+            ui.label(format!("max blur length: {} px", self.params.max_length));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.max_length, 1.0..=200.0))
+                .drag_stopped();
+
+            should_update
+        })
+        .inner
+    }
+
+    fn to_params(&self) -> &[u8] {
+        bytemuck::bytes_of(&self.params)
+    }
+}
+
+impl MotionBlur {
+    pub fn new() -> MotionBlur {
+        MotionBlur {
+            pipeline: None,
+            params: MotionBlurParams {
+                shutter_angle: 180.0,
+                samples: 16,
+                max_length: 64.0,
+                _padding: 0,
+            },
+        }
+    }
+}
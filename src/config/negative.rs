@@ -1,4 +1,5 @@
 use crate::{config::PostEffect, renderer};
+use std::sync::{Arc, Mutex};
 
 pub struct Negative {
     pipeline: Option<egui_wgpu::wgpu::RenderPipeline>,
@@ -14,6 +15,10 @@ impl PostEffect for Negative {
         super::PostEffectEnum::Negative
     }
 
+    fn duplicate(&self) -> Arc<Mutex<dyn PostEffect + Send>> {
+        Arc::new(Mutex::new(Negative::new()))
+    }
+
     fn get_pipeline(
         &mut self,
         device: &eframe::wgpu::Device,
@@ -1,4 +1,4 @@
-use crate::config::{ShadingModel, ShadingModelEnum};
+use crate::config::{ShadingModel, ShadingModelEnum, VertexColorMode};
 use eframe::egui_wgpu::wgpu;
 
 #[repr(C)]
@@ -8,6 +8,8 @@ pub struct Phong {
     kd: f32,
     ks: f32,
     alph: f32,
+    vertex_color: u32,
+    _padding: [u32; 3],
 }
 
 impl Phong {
@@ -17,6 +19,8 @@ impl Phong {
             kd: 0.4,
             ks: 0.4,
             alph: 4.0,
+            vertex_color: VertexColorMode::Multiply as u32,
+            _padding: [0; 3],
         }
     }
 }
@@ -47,6 +51,8 @@ impl ShadingModel for Phong {
                 .add(egui::Slider::new(&mut self.alph, 0.0..=100.0))
                 .drag_stopped();
 
+            should_update |= VertexColorMode::build_widget(ui, &mut self.vertex_color);
+
             should_update
         })
         .inner
@@ -101,6 +107,15 @@ impl ShadingModel for Phong {
         (phong_bind_group_layout, phong_bind_group, phong_buffer)
     }
 
+    fn set_metallic_roughness(&mut self, metallic: f32, roughness: f32) {
+        // Metals reflect rather than scatter. The shininess is the Blinn-Phong exponent
+        // matching a GGX lobe of width roughness².
+        self.kd = 0.8 * (1.0 - 0.5 * metallic);
+        self.ks = 0.04 + 0.76 * metallic;
+        let alpha = roughness.max(0.05).powi(2);
+        self.alph = (2.0 / (alpha * alpha) - 2.0).clamp(1.0, 100.0);
+    }
+
     fn to_params(&self) -> &[u8] {
         bytemuck::cast_ref::<_, [u8; size_of::<Phong>()]>(self)
     }
@@ -0,0 +1,93 @@
+use eframe::egui_wgpu::wgpu;
+
+/// Rasterizer state of the scene pipelines, plus the wireframe overlay drawn over them.
+#[derive(Clone, Copy)]
+pub struct PipelineSettings {
+    pub cull_mode: Option<wgpu::Face>,
+    pub front_face: wgpu::FrontFace,
+    pub polygon_mode: wgpu::PolygonMode,
+    pub wireframe: bool,
+    pub wireframe_color: [f32; 4],
+    pub wireframe_width: f32,
+}
+
+impl PipelineSettings {
+    pub fn new() -> PipelineSettings {
+        PipelineSettings {
+            cull_mode: None,
+            front_face: wgpu::FrontFace::Cw,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            wireframe: false,
+            wireframe_color: [0.0, 0.0, 0.0, 1.0],
+            wireframe_width: 1.0,
+        }
+    }
+
+    /// Returns true when a setting baked into the pipeline changed and it has to be rebuilt.
+    /// `features` are the device's, which decide the available polygon modes.
+    pub fn build_widget(&mut self, ui: &mut egui::Ui, features: wgpu::Features) -> bool {
+        ui.vertical(|ui| {
+            let mut should_update = false;
+            ui.style_mut().spacing.slider_width = ui.available_width();
+
+            ui.horizontal(|ui| {
+                ui.label("cull mode:");
+                for (mode, name) in [
+                    (None, "None"),
+                    (Some(wgpu::Face::Front), "Front"),
+                    (Some(wgpu::Face::Back), "Back"),
+                ] {
+                    should_update |= ui
+                        .selectable_value(&mut self.cull_mode, mode, name)
+                        .changed();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("front face:");
+                for (face, name) in [(wgpu::FrontFace::Cw, "CW"), (wgpu::FrontFace::Ccw, "CCW")] {
+                    should_update |= ui
+                        .selectable_value(&mut self.front_face, face, name)
+                        .changed();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("polygon mode:");
+                for (mode, name, feature) in [
+                    (wgpu::PolygonMode::Fill, "Fill", wgpu::Features::empty()),
+                    (
+                        wgpu::PolygonMode::Line,
+                        "Line",
+                        wgpu::Features::POLYGON_MODE_LINE,
+                    ),
+                    (
+                        wgpu::PolygonMode::Point,
+                        "Point",
+                        wgpu::Features::POLYGON_MODE_POINT,
+                    ),
+                ] {
+                    ui.add_enabled_ui(features.contains(feature), |ui| {
+                        should_update |= ui
+                            .selectable_value(&mut self.polygon_mode, mode, name)
+                            .on_disabled_hover_text("Not supported by this adapter")
+                            .changed();
+                    });
+                }
+            });
+
+            ui.checkbox(&mut self.wireframe, "wireframe overlay");
+            if self.wireframe {
+                ui.horizontal(|ui| {
+                    ui.label("line colour:");
+                    ui.color_edit_button_rgba_unmultiplied(&mut self.wireframe_color);
+                });
+                ui.label(format!("line width: {}", self.wireframe_width));
+                ui.add(egui::Slider::new(&mut self.wireframe_width, 0.5..=5.0));
+            }
+
+            should_update
+        })
+        .inner
+    }
+}
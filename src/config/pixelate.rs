@@ -0,0 +1,78 @@
+use crate::{config::PostEffect, renderer};
+use std::sync::{Arc, Mutex};
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PixelateParams {
+    pixel_size: f32,
+    _padding: [u32; 3],
+}
+
+pub struct Pixelate {
+    pipeline: Option<egui_wgpu::wgpu::RenderPipeline>,
+    params: PixelateParams,
+}
+
+impl PostEffect for Pixelate {
+    fn get_source(&self) -> String {
+        let _path: std::path::PathBuf = ["post", "pixelate.wgsl"].iter().collect();
+        include_str!("post/pixelate.wgsl").into()
+    }
+
+    fn get_pipeline(
+        &mut self,
+        device: &eframe::wgpu::Device,
+        target_format: eframe::wgpu::TextureFormat,
+    ) -> &egui_wgpu::wgpu::RenderPipeline {
+        if self.pipeline.is_none() {
+            self.pipeline = Some(renderer::create_post_pipeline(
+                device,
+                target_format,
+                self.get_source(),
+            ));
+        }
+        self.pipeline.as_ref().unwrap()
+    }
+
+    fn as_enum(&self) -> super::PostEffectEnum {
+        super::PostEffectEnum::Pixelate
+    }
+
+    fn duplicate(&self) -> Arc<Mutex<dyn PostEffect + Send>> {
+        Arc::new(Mutex::new(Pixelate {
+            pipeline: None,
+            params: self.params,
+        }))
+    }
+
+    fn build_widget(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.vertical(|ui| {
+            let mut should_update = false;
+            ui.style_mut().spacing.slider_width = ui.available_width();
+
+            ui.label(format!("pixel size: {}", self.params.pixel_size));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.pixel_size, 1.0..=64.0).step_by(1.0))
+                .drag_stopped();
+
+            should_update
+        })
+        .inner
+    }
+
+    fn to_params(&self) -> &[u8] {
+        bytemuck::bytes_of(&self.params)
+    }
+}
+
+impl Pixelate {
+    pub fn new() -> Pixelate {
+        Pixelate {
+            pipeline: None,
+            params: PixelateParams {
+                pixel_size: 8.0,
+                _padding: [0; 3],
+            },
+        }
+    }
+}
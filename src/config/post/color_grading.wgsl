@@ -0,0 +1,65 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var post_texture: texture_2d<f32>;
+@group(0) @binding(1) var post_sampler: sampler;
+
+struct ColorGradingParams {
+    lift: vec4<f32>,
+    gamma: vec4<f32>,
+    gain: vec4<f32>,
+    domain_min: vec4<f32>,
+    domain_max: vec4<f32>,
+    strength: f32,
+    saturation: f32,
+    contrast: f32,
+    use_lut: u32,
+}
+@group(2) @binding(0) var<uniform> params: ColorGradingParams;
+
+@group(3) @binding(0) var lut_texture: texture_3d<f32>;
+@group(3) @binding(1) var lut_sampler: sampler;
+
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+fn apply_lut(color: vec3<f32>) -> vec3<f32> {
+    let size = f32(textureDimensions(lut_texture).x);
+    let domain = params.domain_max.rgb - params.domain_min.rgb;
+    let t = clamp((color - params.domain_min.rgb) / domain, vec3<f32>(0.0), vec3<f32>(1.0));
+    // Map [0, 1] onto the first and last texel centres so filtering is trilinear
+    // between LUT entries rather than clamped at the edges.
+    let coord = t * ((size - 1.0) / size) + 0.5 / size;
+    return textureSampleLevel(lut_texture, lut_sampler, coord, 0.0).rgb;
+}
+
+fn apply_grade(color: vec3<f32>) -> vec3<f32> {
+    var c = params.gain.rgb * (color + params.lift.rgb * (1.0 - color));
+    c = pow(max(c, vec3<f32>(0.0)), 1.0 / max(params.gamma.rgb, vec3<f32>(1e-3)));
+    c = mix(vec3<f32>(luminance(c)), c, params.saturation);
+    return (c - 0.5) * params.contrast + 0.5;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(post_texture, post_sampler, in.uv);
+    if params.use_lut != 0u {
+        let graded = apply_lut(color.rgb);
+        return vec4<f32>(mix(color.rgb, graded, params.strength), color.a);
+    }
+    return vec4<f32>(apply_grade(color.rgb), color.a);
+}
@@ -0,0 +1,62 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var post_texture: texture_2d<f32>;
+@group(0) @binding(1) var post_sampler: sampler;
+
+struct FrameUniform {
+    time: f32,
+    delta_time: f32,
+    frame: u32,
+}
+@group(1) @binding(3) var<uniform> frame: FrameUniform;
+
+struct CrtParams {
+    curvature: f32,
+    scanline_intensity: f32,
+    scanline_count: f32,
+    mask_intensity: f32,
+    flicker: f32,
+}
+@group(2) @binding(0) var<uniform> params: CrtParams;
+
+const PI: f32 = 3.14159265;
+
+fn barrel(uv: vec2<f32>) -> vec2<f32> {
+    let centered = uv * 2.0 - 1.0;
+    let distorted = centered * (1.0 + params.curvature * dot(centered, centered));
+    return distorted * 0.5 + 0.5;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let uv = barrel(in.uv);
+    if any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+    var color = textureSampleLevel(post_texture, post_sampler, uv, 0.0);
+
+    let scanline = pow(sin(uv.y * params.scanline_count * PI), 2.0);
+    color = vec4<f32>(color.rgb * (1.0 - params.scanline_intensity * (1.0 - scanline)), color.a);
+
+    // Aperture grille: every third column favours one of the phosphor colours.
+    var mask = vec3<f32>(1.0 - params.mask_intensity);
+    mask[u32(in.position.x) % 3u] = 1.0;
+    color = vec4<f32>(color.rgb * mask, color.a);
+
+    let flicker = 1.0 + params.flicker * sin(frame.time * 120.0);
+    return vec4<f32>(color.rgb * flicker, color.a);
+}
@@ -0,0 +1,70 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var post_texture: texture_2d<f32>;
+@group(0) @binding(1) var post_sampler: sampler;
+
+struct DitherParams {
+    palette: array<vec4<f32>, 8>,
+    palette_size: u32,
+    levels: f32,
+    spread: f32,
+    pixel_size: f32,
+}
+@group(2) @binding(0) var<uniform> params: DitherParams;
+
+const BAYER_4X4 = array<f32, 16>(
+    0.0, 8.0, 2.0, 10.0,
+    12.0, 4.0, 14.0, 6.0,
+    3.0, 11.0, 1.0, 9.0,
+    15.0, 7.0, 13.0, 5.0,
+);
+
+fn nearest_palette_color(color: vec3<f32>) -> vec3<f32> {
+    var best = params.palette[0].rgb;
+    var best_distance = distance(color, best);
+    for (var i = 1u; i < params.palette_size; i++) {
+        let candidate = params.palette[i].rgb;
+        let d = distance(color, candidate);
+        if d < best_distance {
+            best = candidate;
+            best_distance = d;
+        }
+    }
+    return best;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(post_texture));
+    let block = max(params.pixel_size, 1.0);
+    let cell = floor(in.position.xy / block);
+    let color = textureSampleLevel(post_texture, post_sampler, (cell + 0.5) * block / size, 0.0);
+
+    var bayer = BAYER_4X4;
+    let index = u32(cell.y) % 4u * 4u + u32(cell.x) % 4u;
+    let threshold = ((bayer[index] + 0.5) / 16.0 - 0.5) * params.spread;
+
+    if params.palette_size == 0u {
+        let steps = max(params.levels, 2.0) - 1.0;
+        let quantized = floor(color.rgb * steps + 0.5 + threshold) / steps;
+        return vec4<f32>(clamp(quantized, vec3<f32>(0.0), vec3<f32>(1.0)), color.a);
+    }
+
+    // Offset by the threshold scaled to the typical gap between palette entries.
+    let gap = 1.0 / f32(params.palette_size - 1u);
+    return vec4<f32>(nearest_palette_color(color.rgb + threshold * gap), color.a);
+}
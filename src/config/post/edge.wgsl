@@ -0,0 +1,112 @@
+const SOURCE_DEPTH: u32 = 1u;
+const SOURCE_NORMAL: u32 = 2u;
+const SOURCE_COLOR: u32 = 4u;
+
+const SOBEL_X = array<f32, 9>(-1.0, 0.0, 1.0, -2.0, 0.0, 2.0, -1.0, 0.0, 1.0);
+const SOBEL_Y = array<f32, 9>(-1.0, -2.0, -1.0, 0.0, 0.0, 0.0, 1.0, 2.0, 1.0);
+const ROBERTS_X = array<f32, 9>(0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, -1.0);
+const ROBERTS_Y = array<f32, 9>(0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, -1.0, 0.0);
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var post_texture: texture_2d<f32>;
+@group(0) @binding(1) var post_sampler: sampler;
+
+struct CameraUniform {
+    proj: mat4x4<f32>,
+    view: mat4x4<f32>,
+    inv_proj: mat4x4<f32>,
+    inv_view: mat4x4<f32>,
+    view_proj: mat4x4<f32>,
+    prev_view_proj: mat4x4<f32>,
+    jitter: vec4<f32>,
+};
+@group(1) @binding(0) var depth_texture: texture_depth_2d;
+@group(1) @binding(1) var normal_texture: texture_2d<f32>;
+@group(1) @binding(2) var<uniform> camera: CameraUniform;
+
+struct EdgeParams {
+    color: vec4<f32>,
+    thickness: f32,
+    depth_threshold: f32,
+    normal_threshold: f32,
+    color_threshold: f32,
+    kernel: u32,
+    sources: u32,
+}
+@group(2) @binding(0) var<uniform> params: EdgeParams;
+
+// Distance along the view axis, for perspective and orthographic projections alike.
+// Gradients are divided by it, which keeps the depth threshold independent of scale.
+fn view_distance(coord: vec2<i32>) -> f32 {
+    let d = textureLoad(depth_texture, coord, 0);
+    let view = camera.inv_proj * vec4<f32>(0.0, 0.0, d, 1.0);
+    return max(view.z / view.w, 1e-6);
+}
+
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<i32>(textureDimensions(depth_texture));
+    let center = vec2<i32>(in.position.xy);
+    let step = max(i32(round(params.thickness)), 1);
+
+    var kx = SOBEL_X;
+    var ky = SOBEL_Y;
+    if params.kernel == 1u {
+        kx = ROBERTS_X;
+        ky = ROBERTS_Y;
+    }
+
+    var depth_grad = vec2<f32>(0.0);
+    var normal_gx = vec3<f32>(0.0);
+    var normal_gy = vec3<f32>(0.0);
+    var color_grad = vec2<f32>(0.0);
+    for (var i = 0; i < 9; i++) {
+        let offset = vec2<i32>(i % 3 - 1, i / 3 - 1) * step;
+        let coord = clamp(center + offset, vec2<i32>(0), size - 1);
+        let w = vec2<f32>(kx[i], ky[i]);
+
+        depth_grad += w * view_distance(coord);
+        let normal = textureLoad(normal_texture, coord, 0).xyz;
+        normal_gx += w.x * normal;
+        normal_gy += w.y * normal;
+        color_grad += w * luminance(textureLoad(post_texture, coord, 0).rgb);
+    }
+
+    let depth_edge = length(depth_grad) / view_distance(clamp(center, vec2<i32>(0), size - 1));
+    let normal_edge = sqrt(dot(normal_gx, normal_gx) + dot(normal_gy, normal_gy));
+    let color_edge = length(color_grad);
+
+    var edge = false;
+    if (params.sources & SOURCE_DEPTH) != 0u {
+        edge = edge || depth_edge > params.depth_threshold;
+    }
+    if (params.sources & SOURCE_NORMAL) != 0u {
+        edge = edge || normal_edge > params.normal_threshold;
+    }
+    if (params.sources & SOURCE_COLOR) != 0u {
+        edge = edge || color_edge > params.color_threshold;
+    }
+
+    let color = textureSample(post_texture, post_sampler, in.uv);
+    let coverage = select(0.0, params.color.a, edge);
+    return vec4<f32>(mix(color.rgb, params.color.rgb, coverage), color.a);
+}
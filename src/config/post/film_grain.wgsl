@@ -0,0 +1,61 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var post_texture: texture_2d<f32>;
+@group(0) @binding(1) var post_sampler: sampler;
+
+struct FrameUniform {
+    time: f32,
+    delta_time: f32,
+    frame: u32,
+}
+@group(1) @binding(3) var<uniform> frame: FrameUniform;
+
+struct FilmGrainParams {
+    intensity: f32,
+    grain_size: f32,
+    luminance_response: f32,
+    frame_rate: f32,
+    colored: u32,
+}
+@group(2) @binding(0) var<uniform> params: FilmGrainParams;
+
+fn pcg(v: u32) -> u32 {
+    let state = v * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn noise(cell: vec2<u32>, seed: u32) -> f32 {
+    return f32(pcg(cell.x ^ pcg(cell.y ^ pcg(seed)))) / 4294967295.0 - 0.5;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(post_texture, post_sampler, in.uv);
+
+    let cell = vec2<u32>(in.position.xy / max(params.grain_size, 1.0));
+    let seed = u32(frame.time * params.frame_rate) * 3u;
+    var grain = vec3<f32>(noise(cell, seed));
+    if params.colored != 0u {
+        grain = vec3<f32>(grain.r, noise(cell, seed + 1u), noise(cell, seed + 2u));
+    }
+
+    // Real grain is most visible in the shadows and midtones.
+    let luma = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    let response = mix(1.0, 1.0 - luma, params.luminance_response);
+    return vec4<f32>(color.rgb + grain * params.intensity * response, color.a);
+}
@@ -0,0 +1,109 @@
+const MODE_LINEAR: u32 = 1u;
+const MODE_EXP: u32 = 2u;
+const MODE_EXP2: u32 = 3u;
+
+const FLAG_HEIGHT: u32 = 1u;
+const FLAG_BACKGROUND: u32 = 2u;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var post_texture: texture_2d<f32>;
+@group(0) @binding(1) var post_sampler: sampler;
+
+struct CameraUniform {
+    proj: mat4x4<f32>,
+    view: mat4x4<f32>,
+    inv_proj: mat4x4<f32>,
+    inv_view: mat4x4<f32>,
+    view_proj: mat4x4<f32>,
+    prev_view_proj: mat4x4<f32>,
+    jitter: vec4<f32>,
+};
+@group(1) @binding(0) var depth_texture: texture_depth_2d;
+@group(1) @binding(2) var<uniform> camera: CameraUniform;
+
+struct FogParams {
+    color: vec4<f32>,
+    mode: u32,
+    density: f32,
+    start: f32,
+    end: f32,
+    height_density: f32,
+    height_falloff: f32,
+    height_base: f32,
+    flags: u32,
+}
+@group(2) @binding(0) var<uniform> params: FogParams;
+
+fn distance_fog(distance: f32) -> f32 {
+    let d = max(distance - params.start, 0.0);
+    switch params.mode {
+        case MODE_LINEAR: {
+            return clamp(d / max(params.end - params.start, 1e-4), 0.0, 1.0);
+        }
+        case MODE_EXP: {
+            return 1.0 - exp(-params.density * d);
+        }
+        case MODE_EXP2: {
+            let x = params.density * d;
+            return 1.0 - exp(-x * x);
+        }
+        default: {
+            return 0.0;
+        }
+    }
+}
+
+// Density falls off as exp(-falloff * (y - base)); integrated analytically along
+// the view ray from the camera to the shaded point.
+fn height_fog(origin: vec3<f32>, ray: vec3<f32>) -> f32 {
+    let b = max(params.height_falloff, 1e-4);
+    let distance = length(ray);
+    let dir_y = ray.y / max(distance, 1e-6);
+    let at_origin = params.height_density * exp(-b * (origin.y - params.height_base));
+    var amount = at_origin * distance;
+    if abs(dir_y) > 1e-4 {
+        amount = at_origin * (1.0 - exp(-b * dir_y * distance)) / (b * dir_y);
+    }
+    return 1.0 - exp(-max(amount, 0.0));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(post_texture, post_sampler, in.uv);
+
+    let size = textureDimensions(depth_texture);
+    let depth = textureLoad(depth_texture, min(vec2<u32>(in.position.xy), size - 1u), 0);
+    if depth >= 1.0 && (params.flags & FLAG_BACKGROUND) == 0u {
+        return color;
+    }
+
+    // Reconstruct the view and world space position of the pixel from depth.
+    let ndc = vec4<f32>(in.uv.x * 2.0 - 1.0, 1.0 - in.uv.y * 2.0, depth, 1.0);
+    let view_h = camera.inv_proj * ndc;
+    let view_pos = view_h.xyz / view_h.w;
+    let world_pos = (camera.inv_view * vec4<f32>(view_pos, 1.0)).xyz;
+    let camera_pos = camera.inv_view[3].xyz;
+
+    var clear = 1.0 - distance_fog(length(view_pos));
+    if (params.flags & FLAG_HEIGHT) != 0u {
+        clear *= 1.0 - height_fog(camera_pos, world_pos - camera_pos);
+    }
+
+    let fog = (1.0 - clear) * params.color.a;
+    return vec4<f32>(mix(color.rgb, params.color.rgb, fog), color.a);
+}
@@ -0,0 +1,70 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var post_texture: texture_2d<f32>;
+@group(0) @binding(1) var post_sampler: sampler;
+
+struct CameraUniform {
+    proj: mat4x4<f32>,
+    view: mat4x4<f32>,
+    inv_proj: mat4x4<f32>,
+    inv_view: mat4x4<f32>,
+    view_proj: mat4x4<f32>,
+    prev_view_proj: mat4x4<f32>,
+    jitter: vec4<f32>,
+};
+@group(1) @binding(0) var depth_texture: texture_depth_2d;
+@group(1) @binding(2) var<uniform> camera: CameraUniform;
+
+struct MotionBlurParams {
+    shutter_angle: f32,
+    samples: u32,
+    max_length: f32,
+}
+@group(2) @binding(0) var<uniform> params: MotionBlurParams;
+
+fn clip_to_uv(clip: vec4<f32>) -> vec2<f32> {
+    let ndc = clip.xy / clip.w;
+    return vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(post_texture));
+    let depth = textureLoad(depth_texture, vec2<i32>(in.position.xy), 0);
+
+    // Screen-space velocity of the surface under the pixel, caused by camera motion
+    // since the previous frame.
+    let ndc = vec4<f32>(in.uv.x * 2.0 - 1.0, 1.0 - in.uv.y * 2.0, depth, 1.0);
+    let view_h = camera.inv_proj * ndc;
+    let world = camera.inv_view * vec4<f32>(view_h.xyz / view_h.w, 1.0);
+    var velocity = clip_to_uv(camera.view_proj * world) - clip_to_uv(camera.prev_view_proj * world);
+
+    // The shutter is open for shutter_angle / 360 of the frame.
+    velocity *= params.shutter_angle / 360.0;
+    let length_px = length(velocity * size);
+    if length_px > params.max_length {
+        velocity *= params.max_length / length_px;
+    }
+
+    let samples = max(params.samples, 2u);
+    var color = vec4<f32>(0.0);
+    for (var i = 0u; i < samples; i++) {
+        let t = f32(i) / f32(samples - 1u) - 0.5;
+        color += textureSampleLevel(post_texture, post_sampler, in.uv + velocity * t, 0.0);
+    }
+    return color / f32(samples);
+}
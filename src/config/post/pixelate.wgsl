@@ -0,0 +1,31 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var post_texture: texture_2d<f32>;
+@group(0) @binding(1) var post_sampler: sampler;
+
+struct PixelateParams {
+    pixel_size: f32,
+}
+@group(2) @binding(0) var<uniform> params: PixelateParams;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(post_texture));
+    let block = max(params.pixel_size, 1.0);
+    let center = (floor(in.position.xy / block) + 0.5) * block;
+    return textureSampleLevel(post_texture, post_sampler, center / size, 0.0);
+}
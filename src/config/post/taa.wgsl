@@ -0,0 +1,94 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var post_texture: texture_2d<f32>;
+@group(0) @binding(1) var post_sampler: sampler;
+
+struct CameraUniform {
+    proj: mat4x4<f32>,
+    view: mat4x4<f32>,
+    inv_proj: mat4x4<f32>,
+    inv_view: mat4x4<f32>,
+    view_proj: mat4x4<f32>,
+    prev_view_proj: mat4x4<f32>,
+    jitter: vec4<f32>,
+};
+@group(1) @binding(0) var depth_texture: texture_depth_2d;
+@group(1) @binding(2) var<uniform> camera: CameraUniform;
+
+struct TaaParams {
+    blend: f32,
+    history_valid: u32,
+    clamp_history: u32,
+}
+@group(2) @binding(0) var<uniform> params: TaaParams;
+
+@group(3) @binding(0) var history_texture: texture_2d<f32>;
+@group(3) @binding(1) var history_sampler: sampler;
+
+fn clip_to_uv(clip: vec4<f32>) -> vec2<f32> {
+    let ndc = clip.xy / clip.w;
+    return vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<i32>(textureDimensions(post_texture));
+    let center = vec2<i32>(in.position.xy);
+    let current = textureLoad(post_texture, center, 0);
+
+    // Colour bounds of the neighbourhood for clamping, and the nearest depth in it
+    // so edges take the motion of the foreground surface.
+    var min_color = current.rgb;
+    var max_color = current.rgb;
+    var closest_depth = 1.0;
+    var closest = center;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let coord = clamp(center + vec2<i32>(x, y), vec2<i32>(0), size - 1);
+            let color = textureLoad(post_texture, coord, 0).rgb;
+            min_color = min(min_color, color);
+            max_color = max(max_color, color);
+            let depth = textureLoad(depth_texture, coord, 0);
+            if depth < closest_depth {
+                closest_depth = depth;
+                closest = coord;
+            }
+        }
+    }
+
+    if params.history_valid == 0u {
+        return current;
+    }
+
+    // Motion vector from reprojecting the surface with last frame's view-projection.
+    let uv = (vec2<f32>(closest) + 0.5) / vec2<f32>(size);
+    let ndc = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, closest_depth, 1.0);
+    let view_h = camera.inv_proj * ndc;
+    let world = camera.inv_view * vec4<f32>(view_h.xyz / view_h.w, 1.0);
+    let velocity = clip_to_uv(camera.view_proj * world) - clip_to_uv(camera.prev_view_proj * world);
+
+    let history_uv = in.uv - velocity;
+    if any(history_uv < vec2<f32>(0.0)) || any(history_uv > vec2<f32>(1.0)) {
+        return current;
+    }
+
+    var history = textureSampleLevel(history_texture, history_sampler, history_uv, 0.0).rgb;
+    if params.clamp_history != 0u {
+        history = clamp(history, min_color, max_color);
+    }
+    return vec4<f32>(mix(current.rgb, history, params.blend), current.a);
+}
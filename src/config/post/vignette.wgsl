@@ -0,0 +1,41 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var post_texture: texture_2d<f32>;
+@group(0) @binding(1) var post_sampler: sampler;
+
+struct VignetteParams {
+    color: vec4<f32>,
+    intensity: f32,
+    radius: f32,
+    softness: f32,
+    roundness: f32,
+}
+@group(2) @binding(0) var<uniform> params: VignetteParams;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(post_texture, post_sampler, in.uv);
+    let size = vec2<f32>(textureDimensions(post_texture));
+
+    // A roundness of 1 gives a circle, 0 stretches the vignette to the viewport.
+    var p = in.uv * 2.0 - 1.0;
+    p.x *= mix(1.0, size.x / size.y, params.roundness);
+    let shade = smoothstep(params.radius, params.radius + params.softness, length(p));
+
+    let amount = shade * params.intensity * params.color.a;
+    return vec4<f32>(mix(color.rgb, params.color.rgb, amount), color.a);
+}
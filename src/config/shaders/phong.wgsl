@@ -1,10 +1,22 @@
 struct CameraUniform {
     proj: mat4x4<f32>,
     view: mat4x4<f32>,
+    inv_proj: mat4x4<f32>,
+    inv_view: mat4x4<f32>,
+    view_proj: mat4x4<f32>,
+    prev_view_proj: mat4x4<f32>,
+    jitter: vec4<f32>,
 };
 @group(0) @binding(0)
 var<uniform> camera: CameraUniform;
 
+struct ModelUniform {
+    model: mat4x4<f32>,
+    normal: mat3x3<f32>,
+};
+@group(0) @binding(1)
+var<uniform> model: ModelUniform;
+
 struct Light {
     position: vec3<f32>,
     color: vec3<f32>,
@@ -17,6 +29,8 @@ struct Phong {
     kd: f32,
     ks: f32,
     alph: f32,
+    // 0 ignores the vertex colour, 1 multiplies the texture by it, 2 replaces it.
+    vertex_color: u32,
 }
 @group(2) @binding(0)
 var<uniform> phong: Phong;
@@ -31,6 +45,7 @@ struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
     @location(2) texcoord: vec2<f32>,
+    @location(4) color: vec4<f32>,
 };
 
 struct VertexOutput {
@@ -38,28 +53,44 @@ struct VertexOutput {
     @location(0) world_position: vec3<f32>,
     @location(1) world_normal: vec3<f32>,
     @location(2) texcoord: vec2<f32>,
+    @location(3) color: vec4<f32>,
+};
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) normal: vec4<f32>,
 };
 
 @vertex
 fn vs_main(in: VertexInput) -> VertexOutput {
     var out: VertexOutput;
-    let world_pos = vec4<f32>(in.position, 1.0);
+    let world_pos = model.model * vec4<f32>(in.position, 1.0);
+    let world_normal = model.normal * in.normal;
     out.world_position = (camera.view * world_pos).xyz;
     out.clip_position = camera.proj * camera.view * world_pos;
-    out.world_normal = normalize(camera.view * vec4<f32>(in.normal, 0.0)).xyz;
+    out.world_normal = normalize(camera.view * vec4<f32>(world_normal, 0.0)).xyz;
     out.texcoord = in.texcoord;
+    out.color = in.color;
     return out;
 }
 
 @fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+fn fs_main(in: VertexOutput) -> FragmentOutput {
     let light_pos = (camera.view * vec4<f32>(light.position, 1)).xyz;
     let l = normalize(light_pos - in.world_position);
     let diff = max(0.0, dot(l, in.world_normal));
     let v = normalize(-in.world_position);
     let r = reflect(-l, in.world_normal);
     let spec = pow(max(0.0, dot(v, r)), phong.alph);
-    let base = textureSample(texture, t_sampler, in.texcoord);
+    var base = textureSample(texture, t_sampler, in.texcoord);
+    switch phong.vertex_color {
+        case 1u: { base *= in.color; }
+        case 2u: { base = in.color; }
+        default: {}
+    }
     let color = base.rgb * light.color * (phong.ka + phong.kd*diff + phong.ks*spec);
-    return vec4<f32>(color, base.a);
+    var out: FragmentOutput;
+    out.color = vec4<f32>(color, base.a);
+    out.normal = vec4<f32>(normalize(in.world_normal), 1.0);
+    return out;
 }
\ No newline at end of file
@@ -0,0 +1,236 @@
+use crate::{config::PostEffect, renderer};
+use eframe::egui_wgpu::wgpu;
+use std::sync::{Arc, Mutex};
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct TaaParams {
+    blend: f32,
+    history_valid: u32,
+    clamp_history: u32,
+    _padding: u32,
+}
+
+fn history_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+        label: Some("history_bind_group_layout"),
+    })
+}
+
+/// Temporal anti-aliasing. Relies on the camera jittering its projection every
+/// frame while the effect is in the chain, and keeps its own output as history.
+pub struct Taa {
+    pipeline: Option<wgpu::RenderPipeline>,
+    params: TaaParams,
+    history: Option<wgpu::Texture>,
+    bind_group: Option<wgpu::BindGroup>,
+    /// Frame number and chain position of the last run. The history only matches the
+    /// input when the effect ran on the previous frame in the same place.
+    last_run: Option<(u32, usize)>,
+}
+
+impl PostEffect for Taa {
+    fn get_source(&self) -> String {
+        let _path: std::path::PathBuf = ["post", "taa.wgsl"].iter().collect();
+        include_str!("post/taa.wgsl").into()
+    }
+
+    fn get_pipeline(
+        &mut self,
+        device: &eframe::wgpu::Device,
+        target_format: eframe::wgpu::TextureFormat,
+    ) -> &egui_wgpu::wgpu::RenderPipeline {
+        if self.pipeline.is_none() {
+            self.pipeline = Some(renderer::create_post_pipeline_with_layout(
+                device,
+                target_format,
+                self.get_source(),
+                Some(&history_bind_group_layout(device)),
+            ));
+        }
+        self.pipeline.as_ref().unwrap()
+    }
+
+    fn as_enum(&self) -> super::PostEffectEnum {
+        super::PostEffectEnum::Taa
+    }
+
+    fn duplicate(&self) -> Arc<Mutex<dyn PostEffect + Send>> {
+        Arc::new(Mutex::new(Taa {
+            pipeline: None,
+            params: TaaParams {
+                history_valid: 0,
+                ..self.params
+            },
+            history: None,
+            bind_group: None,
+            last_run: None,
+        }))
+    }
+
+    fn build_widget(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.vertical(|ui| {
+            let mut should_update = false;
+            ui.style_mut().spacing.slider_width = ui.available_width();
+
+            ui.label("Place first in the chain: it resolves the jittered scene.");
+
+            ui.label(format!("history weight: {}", self.params.blend));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.blend, 0.0..=0.98))
+                .drag_stopped();
+
+            let mut clamp_history = self.params.clamp_history != 0;
+            if ui
+                .checkbox(&mut clamp_history, "neighbourhood clamping")
+                .changed()
+            {
+                self.params.clamp_history = clamp_history as u32;
+                should_update = true;
+            }
+
+            if ui.button("Reset history").clicked() {
+                self.params.history_valid = 0;
+                should_update = true;
+            }
+
+            should_update
+        })
+        .inner
+    }
+
+    fn begin_frame(&mut self, frame: u32, position: usize) {
+        if self.last_run != Some((frame.wrapping_sub(1), position)) {
+            self.params.history_valid = 0;
+        }
+        self.last_run = Some((frame, position));
+    }
+
+    fn to_params(&self) -> &[u8] {
+        bytemuck::bytes_of(&self.params)
+    }
+
+    fn get_bind_group(
+        &mut self,
+        device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+    ) -> Option<&wgpu::BindGroup> {
+        if self.bind_group.is_none() {
+            let history = self.history.get_or_insert_with(|| {
+                Self::create_history(
+                    device,
+                    wgpu::Extent3d {
+                        width: 1,
+                        height: 1,
+                        depth_or_array_layers: 1,
+                    },
+                    wgpu::TextureFormat::Rgba8Unorm,
+                )
+            });
+            let view = history.create_view(&wgpu::TextureViewDescriptor::default());
+            let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                mipmap_filter: wgpu::FilterMode::Nearest,
+                ..Default::default()
+            });
+            self.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &history_bind_group_layout(device),
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                ],
+                label: Some("taa_history"),
+            }));
+        }
+        self.bind_group.as_ref()
+    }
+
+    fn history(&self) -> Option<&wgpu::Texture> {
+        self.history.as_ref()
+    }
+
+    fn finish(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        output: &wgpu::Texture,
+    ) {
+        let size = output.size();
+        let history = match &self.history {
+            Some(history) if history.size() == size && history.format() == output.format() => {
+                history
+            }
+            _ => {
+                self.bind_group = None;
+                self.history
+                    .insert(Self::create_history(device, size, output.format()))
+            }
+        };
+        encoder.copy_texture_to_texture(output.as_image_copy(), history.as_image_copy(), size);
+        self.params.history_valid = 1;
+    }
+}
+
+impl Taa {
+    pub fn new() -> Taa {
+        Taa {
+            pipeline: None,
+            params: TaaParams {
+                blend: 0.9,
+                history_valid: 0,
+                clamp_history: 1,
+                _padding: 0,
+            },
+            history: None,
+            bind_group: None,
+            last_run: None,
+        }
+    }
+
+    fn create_history(
+        device: &wgpu::Device,
+        size: wgpu::Extent3d,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("taa_history"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{config::PostEffect, renderer};
+use std::sync::{Arc, Mutex};
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct VignetteParams {
+    color: [f32; 4],
+    intensity: f32,
+    radius: f32,
+    softness: f32,
+    roundness: f32,
+}
+
+pub struct Vignette {
+    pipeline: Option<egui_wgpu::wgpu::RenderPipeline>,
+    params: VignetteParams,
+}
+
+impl PostEffect for Vignette {
+    fn get_source(&self) -> String {
+        let _path: std::path::PathBuf = ["post", "vignette.wgsl"].iter().collect();
+        include_str!("post/vignette.wgsl").into()
+    }
+
+    fn get_pipeline(
+        &mut self,
+        device: &eframe::wgpu::Device,
+        target_format: eframe::wgpu::TextureFormat,
+    ) -> &egui_wgpu::wgpu::RenderPipeline {
+        if self.pipeline.is_none() {
+            self.pipeline = Some(renderer::create_post_pipeline(
+                device,
+                target_format,
+                self.get_source(),
+            ));
+        }
+        self.pipeline.as_ref().unwrap()
+    }
+
+    fn as_enum(&self) -> super::PostEffectEnum {
+        super::PostEffectEnum::Vignette
+    }
+
+    fn duplicate(&self) -> Arc<Mutex<dyn PostEffect + Send>> {
+        Arc::new(Mutex::new(Vignette {
+            pipeline: None,
+            params: self.params,
+        }))
+    }
+
+    fn build_widget(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.vertical(|ui| {
+            let mut should_update = false;
+            ui.style_mut().spacing.slider_width = ui.available_width();
+
+            ui.horizontal(|ui| {
+                ui.label("colour:");
+                should_update |= ui
+                    .color_edit_button_rgba_unmultiplied(&mut self.params.color)
+                    .changed();
+            });
+
+            ui.label(format!("intensity: {}", self.params.intensity));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.intensity, 0.0..=1.0))
+                .drag_stopped();
+
+            ui.label(format!("radius: {}", self.params.radius));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.radius, 0.0..=2.0))
+                .drag_stopped();
+
+            ui.label(format!("softness: {}", self.params.softness));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.softness, 0.01..=2.0))
+                .drag_stopped();
+
+            ui.label(format!("roundness: {}", self.params.roundness));
+            should_update |= ui
+                .add(egui::Slider::new(&mut self.params.roundness, 0.0..=1.0))
+                .drag_stopped();
+
+            should_update
+        })
+        .inner
+    }
+
+    fn to_params(&self) -> &[u8] {
+        bytemuck::bytes_of(&self.params)
+    }
+}
+
+impl Vignette {
+    pub fn new() -> Vignette {
+        Vignette {
+            pipeline: None,
+            params: VignetteParams {
+                color: [0.0, 0.0, 0.0, 1.0],
+                intensity: 0.8,
+                radius: 0.75,
+                softness: 0.6,
+                roundness: 1.0,
+            },
+        }
+    }
+}
@@ -0,0 +1,113 @@
+struct CameraUniform {
+    proj: mat4x4<f32>,
+    view: mat4x4<f32>,
+    inv_proj: mat4x4<f32>,
+    inv_view: mat4x4<f32>,
+    view_proj: mat4x4<f32>,
+    prev_view_proj: mat4x4<f32>,
+    jitter: vec4<f32>,
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct ModelUniform {
+    model: mat4x4<f32>,
+    normal: mat3x3<f32>,
+};
+@group(0) @binding(1)
+var<uniform> model: ModelUniform;
+
+struct DebugView {
+    mode: u32,
+}
+@group(2) @binding(0)
+var<uniform> debug_view: DebugView;
+
+@group(3) @binding(0)
+var texture: texture_2d<f32>;
+@group(3) @binding(1)
+var t_sampler: sampler;
+
+const MODE_WORLD_NORMALS: u32 = 1u;
+const MODE_VIEW_NORMALS: u32 = 2u;
+const MODE_UVS: u32 = 3u;
+const MODE_LINEAR_DEPTH: u32 = 4u;
+const MODE_CHECKER: u32 = 5u;
+const MODE_OVERDRAW: u32 = 6u;
+const MODE_ALBEDO: u32 = 7u;
+
+// Checker squares per unit of UV.
+const CHECKER_SCALE: f32 = 8.0;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) texcoord: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) view_position: vec3<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) view_normal: vec3<f32>,
+    @location(3) texcoord: vec2<f32>,
+};
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) normal: vec4<f32>,
+};
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    let world_pos = model.model * vec4<f32>(in.position, 1.0);
+    let world_normal = model.normal * in.normal;
+    out.view_position = (camera.view * world_pos).xyz;
+    out.clip_position = camera.proj * camera.view * world_pos;
+    out.world_normal = world_normal;
+    out.view_normal = (camera.view * vec4<f32>(world_normal, 0.0)).xyz;
+    out.texcoord = in.texcoord;
+    return out;
+}
+
+fn view_depth(depth: f32) -> f32 {
+    let view = camera.inv_proj * vec4<f32>(0.0, 0.0, depth, 1.0);
+    return view.z / view.w;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    var color = vec3<f32>(1.0, 0.0, 1.0);
+    switch debug_view.mode {
+        case MODE_WORLD_NORMALS: {
+            color = normalize(in.world_normal) * 0.5 + 0.5;
+        }
+        case MODE_VIEW_NORMALS: {
+            color = normalize(in.view_normal) * 0.5 + 0.5;
+        }
+        case MODE_UVS: {
+            color = vec3<f32>(fract(in.texcoord), 0.0);
+        }
+        case MODE_LINEAR_DEPTH: {
+            color = vec3<f32>(in.view_position.z / view_depth(1.0));
+        }
+        case MODE_CHECKER: {
+            let cell = floor(in.texcoord * CHECKER_SCALE);
+            let parity = abs(cell.x + cell.y) % 2.0;
+            color = mix(vec3<f32>(0.2), vec3<f32>(0.8), parity);
+        }
+        case MODE_OVERDRAW: {
+            // Added up per fragment by the blend state; more layers read hotter.
+            color = vec3<f32>(0.1, 0.04, 0.01);
+        }
+        case MODE_ALBEDO: {
+            color = textureSample(texture, t_sampler, in.texcoord).rgb;
+        }
+        default: {}
+    }
+    var out: FragmentOutput;
+    out.color = vec4<f32>(color, 1.0);
+    out.normal = vec4<f32>(normalize(in.view_normal), 1.0);
+    return out;
+}
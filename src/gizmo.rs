@@ -0,0 +1,229 @@
+//! Translate, rotate and scale handles drawn over the viewport.
+
+use crate::{object::Transform, renderer::CameraUniform};
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum GizmoMode {
+    Off,
+    Translate,
+    Rotate,
+    Scale,
+}
+
+impl GizmoMode {
+    pub const ALL: [GizmoMode; 4] = [
+        GizmoMode::Off,
+        GizmoMode::Translate,
+        GizmoMode::Rotate,
+        GizmoMode::Scale,
+    ];
+}
+
+impl std::fmt::Display for GizmoMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GizmoMode::Off => write!(f, "Off"),
+            GizmoMode::Translate => write!(f, "Move"),
+            GizmoMode::Rotate => write!(f, "Rotate"),
+            GizmoMode::Scale => write!(f, "Scale"),
+        }
+    }
+}
+
+/// On-screen length of the handles, in points.
+const HANDLE_LENGTH: f32 = 80.0;
+/// How close the pointer has to be to grab a handle, in points.
+const GRAB_DISTANCE: f32 = 6.0;
+const RING_SEGMENTS: usize = 48;
+const AXIS_COLORS: [egui::Color32; 3] = [
+    egui::Color32::from_rgb(230, 60, 60),
+    egui::Color32::from_rgb(60, 200, 60),
+    egui::Color32::from_rgb(60, 110, 240),
+];
+
+#[derive(Clone, Copy)]
+struct Handle {
+    axis: usize,
+    /// Where on the handle the pointer is, in world space.
+    point: glam::Vec3,
+}
+
+pub struct Gizmo {
+    pub mode: GizmoMode,
+    hovered: Option<Handle>,
+    dragged: Option<Handle>,
+}
+
+impl Gizmo {
+    pub fn new() -> Gizmo {
+        Gizmo {
+            mode: GizmoMode::Translate,
+            hovered: None,
+            dragged: None,
+        }
+    }
+
+    /// Whether the pointer is over or dragging a handle, so the camera should ignore it.
+    pub fn wants_pointer(&self) -> bool {
+        self.hovered.is_some() || self.dragged.is_some()
+    }
+
+    /// Applies drags on the handles to `transform` and draws them over `response`'s
+    /// rect, as seen by `camera`.
+    pub fn show(
+        &mut self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        camera: &CameraUniform,
+        transform: &mut Transform,
+    ) {
+        if self.mode == GizmoMode::Off {
+            self.hovered = None;
+            self.dragged = None;
+            return;
+        }
+
+        let rect = response.rect;
+        let view_proj = glam::Mat4::from_cols_array_2d(&camera.view_proj);
+        let to_screen = |point: glam::Vec3| {
+            let clip = view_proj * point.extend(1.0);
+            (clip.w > 1e-5).then(|| {
+                let ndc = clip.truncate() / clip.w;
+                egui::pos2(
+                    rect.min.x + (ndc.x * 0.5 + 0.5) * rect.width(),
+                    rect.min.y + (0.5 - ndc.y * 0.5) * rect.height(),
+                )
+            })
+        };
+
+        let origin = transform.translation;
+        let camera_right = glam::Mat4::from_cols_array_2d(&camera.inv_view)
+            .x_axis
+            .truncate();
+        let (Some(screen_origin), Some(screen_right)) =
+            (to_screen(origin), to_screen(origin + camera_right))
+        else {
+            return;
+        };
+        // World length that shows up as HANDLE_LENGTH points at the object.
+        let length = HANDLE_LENGTH / (screen_right - screen_origin).length().max(1e-3);
+
+        // Scale acts along the object's own axes, the others along the world's.
+        let rotation = match self.mode {
+            GizmoMode::Scale => transform.rotation_quat(),
+            _ => glam::Quat::IDENTITY,
+        };
+        let axes = [glam::Vec3::X, glam::Vec3::Y, glam::Vec3::Z].map(|axis| rotation * axis);
+        let handles = axes.map(|axis| match self.mode {
+            GizmoMode::Rotate => {
+                let u = axis.any_orthonormal_vector();
+                let v = axis.cross(u);
+                (0..=RING_SEGMENTS)
+                    .map(|i| {
+                        let angle = i as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+                        origin + (u * angle.cos() + v * angle.sin()) * length
+                    })
+                    .collect::<Vec<_>>()
+            }
+            _ => vec![origin, origin + axis * length],
+        });
+
+        if self.dragged.is_none() {
+            self.hovered = response
+                .hover_pos()
+                .and_then(|pointer| Self::nearest_handle(&handles, pointer, to_screen));
+        }
+        if response.drag_started_by(egui::PointerButton::Primary) {
+            self.dragged = self.hovered;
+        }
+        if response.drag_stopped() {
+            self.dragged = None;
+        }
+
+        if let Some(handle) = &mut self.dragged
+            && response.dragged()
+        {
+            let delta = response.drag_delta();
+            let axis = axes[handle.axis];
+            // The drag measured in handle lengths along the handle's screen direction.
+            let along = |from: glam::Vec3, to: glam::Vec3| match (to_screen(from), to_screen(to)) {
+                (Some(from), Some(to)) if (to - from).length_sq() > 1.0 => {
+                    delta.dot(to - from) / (to - from).length_sq()
+                }
+                _ => 0.0,
+            };
+            match self.mode {
+                GizmoMode::Translate => {
+                    transform.translation += axis * along(origin, origin + axis * length) * length;
+                }
+                GizmoMode::Scale => {
+                    let factor = 1.0 + along(origin, origin + axis * length);
+                    transform.scale[handle.axis] =
+                        (transform.scale[handle.axis] * factor).max(0.001);
+                }
+                GizmoMode::Rotate => {
+                    // Moving the pointer one radius along the ring's tangent turns one radian.
+                    let tangent = axis.cross(handle.point - origin);
+                    let angle = along(handle.point, handle.point + tangent);
+                    let turn = glam::Quat::from_axis_angle(axis, angle);
+                    transform.set_rotation_quat(turn * transform.rotation_quat());
+                    handle.point = origin + turn * (handle.point - origin);
+                }
+                GizmoMode::Off => {}
+            }
+        }
+
+        let active = self.dragged.or(self.hovered).map(|handle| handle.axis);
+        let painter = ui.painter_at(rect);
+        for (index, handle) in handles.iter().enumerate() {
+            let color = if active == Some(index) {
+                egui::Color32::YELLOW
+            } else {
+                AXIS_COLORS[index]
+            };
+            let stroke = egui::Stroke::new(2.5, color);
+            let points: Vec<egui::Pos2> = handle.iter().filter_map(|p| to_screen(*p)).collect();
+            match self.mode {
+                GizmoMode::Translate if points.len() == 2 => {
+                    painter.arrow(points[0], points[1] - points[0], stroke);
+                }
+                GizmoMode::Scale if points.len() == 2 => {
+                    painter.line_segment([points[0], points[1]], stroke);
+                    painter.rect_filled(
+                        egui::Rect::from_center_size(points[1], egui::vec2(8.0, 8.0)),
+                        0.0,
+                        color,
+                    );
+                }
+                GizmoMode::Rotate => {
+                    painter.add(egui::Shape::line(points, stroke));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The handle closest to `pointer` within grabbing distance, if any.
+    fn nearest_handle(
+        handles: &[Vec<glam::Vec3>; 3],
+        pointer: egui::Pos2,
+        to_screen: impl Fn(glam::Vec3) -> Option<egui::Pos2>,
+    ) -> Option<Handle> {
+        let mut nearest: Option<(f32, Handle)> = None;
+        for (axis, handle) in handles.iter().enumerate() {
+            for segment in handle.windows(2) {
+                let (Some(a), Some(b)) = (to_screen(segment[0]), to_screen(segment[1])) else {
+                    continue;
+                };
+                let ab = b - a;
+                let t = ((pointer - a).dot(ab) / ab.length_sq().max(1e-6)).clamp(0.0, 1.0);
+                let distance = (a + ab * t).distance(pointer);
+                if distance <= GRAB_DISTANCE && nearest.is_none_or(|(d, _)| distance < d) {
+                    let point = segment[0].lerp(segment[1], t);
+                    nearest = Some((distance, Handle { axis, point }));
+                }
+            }
+        }
+        nearest.map(|(_, handle)| handle)
+    }
+}
@@ -1,35 +1,57 @@
+mod bookmarks;
 mod camera;
 mod config;
+mod gizmo;
+mod mesh;
 mod object;
 mod renderer;
+mod texture_viewer;
+mod watch;
 
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
 use eframe::egui_wgpu;
 
 pub struct App {
     shader_conf: config::ShaderConfig,
-    object: object::Object,
+    objects: Vec<object::Object>,
+    /// Index into `objects` of the one the panel and the gizmo edit.
+    selected: usize,
+    next_object_id: u64,
     camera: camera::WorldCamera,
+    bookmarks: bookmarks::Bookmarks,
     light: renderer::LightUniform,
     viewport_size: Option<egui::Vec2>,
+    texture_viewer: texture_viewer::TextureViewer,
+    gizmo: gizmo::Gizmo,
 }
 
 impl App {
     pub fn new<'a>(cc: &'a eframe::CreationContext<'a>) -> Option<Self> {
         let wgpu_render_state = cc.wgpu_render_state.as_ref()?;
-        renderer::build_pipeline(wgpu_render_state, &None, &config::phong::Phong::new());
+        let pipeline = config::pipeline::PipelineSettings::new();
+        renderer::build_pipeline(wgpu_render_state, &pipeline);
+        let mut object = object::Object::new(0);
+        let bounds = object.load(wgpu_render_state);
+        let mut camera = camera::WorldCamera::new();
+        camera.frame(&bounds);
 
         Some(Self {
-            camera: camera::WorldCamera::new(),
+            camera,
+            bookmarks: bookmarks::Bookmarks::new(),
             light: renderer::LightUniform::new(),
-            object: object::Object::default(),
+            objects: vec![object],
+            selected: 0,
+            next_object_id: 1,
             shader_conf: config::ShaderConfig {
-                active_model: Arc::new(Mutex::new(config::phong::Phong::new())),
                 active_post_effects: Vec::new(),
                 selected_effect: None,
+                view_mode: config::ViewMode::Shaded,
+                pipeline,
             },
             viewport_size: None,
+            texture_viewer: texture_viewer::TextureViewer::new(),
+            gizmo: gizmo::Gizmo::new(),
         })
     }
 }
@@ -52,6 +74,10 @@ impl eframe::App for App {
                         self.viewport_size = Some(size);
                         if let Some(rs) = frame.wgpu_render_state() {
                             renderer::post_effect_init(rs, size_in_pixels);
+                            self.texture_viewer.set_texture(
+                                renderer::inspect::inspector_init(rs, size_in_pixels),
+                                size_in_pixels,
+                            );
                         }
                     }
                     self.camera.resize(size.x / size.y);
@@ -59,56 +85,200 @@ impl eframe::App for App {
                 })
             });
 
+        self.camera.update(ctx.input(|i| i.stable_dt));
+        if let Some(rs) = frame.wgpu_render_state()
+            && let Some(point) = renderer::pick::take_picked_point(rs)
+        {
+            self.camera.set_target(point);
+        }
+
         if viewport_response.response.contains_pointer() {
-            if ctx.input(|i| i.key_down(egui::Key::W)) {
-                self.camera.forward();
-            }
-            if ctx.input(|i| i.key_down(egui::Key::S)) {
-                self.camera.backward();
-            }
-            if ctx.input(|i| i.key_down(egui::Key::D)) {
-                self.camera.right();
-            }
-            if ctx.input(|i| i.key_down(egui::Key::A)) {
-                self.camera.left();
-            }
-            if ctx.input(|i| i.key_down(egui::Key::Space)) {
-                self.camera.up();
+            if self.camera.mode() == camera::CameraMode::Fly {
+                let dt = ctx.input(|i| {
+                    // Ctrl sprints and Alt slows down; shift is already taken by down.
+                    let factor = if i.modifiers.ctrl {
+                        camera::WorldCamera::SPRINT_FACTOR
+                    } else if i.modifiers.alt {
+                        camera::WorldCamera::SLOW_FACTOR
+                    } else {
+                        1.0
+                    };
+                    // Capped so a stalled frame doesn't fling the camera away.
+                    i.stable_dt.min(0.1) * factor
+                });
+                if ctx.input(|i| i.key_down(egui::Key::W)) {
+                    self.camera.forward(dt);
+                }
+                if ctx.input(|i| i.key_down(egui::Key::S)) {
+                    self.camera.backward(dt);
+                }
+                if ctx.input(|i| i.key_down(egui::Key::D)) {
+                    self.camera.right(dt);
+                }
+                if ctx.input(|i| i.key_down(egui::Key::A)) {
+                    self.camera.left(dt);
+                }
+                if ctx.input(|i| i.key_down(egui::Key::Space)) {
+                    self.camera.up(dt);
+                }
+                if ctx.input(|i| i.raw.modifiers.shift) {
+                    self.camera.down(dt);
+                }
             }
-            if ctx.input(|i| i.raw.modifiers.shift) {
-                self.camera.down();
+            if self.camera.roll_enabled {
+                let dt = ctx.input(|i| i.stable_dt.min(0.1));
+                if ctx.input(|i| i.key_down(egui::Key::Q)) {
+                    self.camera.roll(-1.0, dt);
+                }
+                if ctx.input(|i| i.key_down(egui::Key::E)) {
+                    self.camera.roll(1.0, dt);
+                }
             }
 
+            let viewport_height = viewport_response.response.rect.height();
             ctx.input(|i| {
-                if i.pointer.primary_down() {
+                if i.pointer.primary_down() && !self.gizmo.wants_pointer() {
                     self.camera.mouse_moved(i.pointer.delta());
                 }
+                let scroll = i.smooth_scroll_delta.y;
+                match self.camera.mode() {
+                    camera::CameraMode::Fly => {
+                        if scroll != 0.0 {
+                            self.camera.adjust_speed(scroll);
+                        }
+                    }
+                    camera::CameraMode::Orbit => {
+                        if i.pointer.middle_down() {
+                            self.camera.pan(i.pointer.delta(), viewport_height);
+                        }
+                        if scroll != 0.0 {
+                            self.camera.dolly(scroll);
+                        }
+                    }
+                }
             });
         }
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            let current = &mut self.shader_conf.active_model.lock().unwrap().as_enum();
-            egui::ComboBox::from_label("Select active shading model!")
+            egui::CollapsingHeader::new("Scene")
+                .default_open(true)
+                .show(ui, |ui| {
+                    let mut remove = None;
+                    for (index, object) in self.objects.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut object.visible, "")
+                                .on_hover_text("Visible");
+                            if ui
+                                .selectable_label(index == self.selected, object.name())
+                                .clicked()
+                            {
+                                self.selected = index;
+                            }
+                            if object.is_loading() {
+                                ui.spinner();
+                            }
+                            if ui.button("[X]").clicked() {
+                                remove = Some(index);
+                            }
+                        });
+                    }
+                    // The panel always edits some object, so the last one stays.
+                    if let Some(index) = remove
+                        && self.objects.len() > 1
+                    {
+                        let object = self.objects.remove(index);
+                        if let Some(rs) = frame.wgpu_render_state() {
+                            renderer::remove_object(rs, object.id);
+                        }
+                        if self.selected >= index && self.selected > 0 {
+                            self.selected -= 1;
+                        }
+                    }
+                    if ui.button("[+]").on_hover_text("Add object").clicked()
+                        && let Some(rs) = frame.wgpu_render_state()
+                    {
+                        let mut object = object::Object::new(self.next_object_id);
+                        self.next_object_id += 1;
+                        object.load(rs);
+                        self.objects.push(object);
+                        self.selected = self.objects.len() - 1;
+                    }
+                });
+
+            let object = &mut self.objects[self.selected];
+            let mut current = object.shading_model.lock().unwrap().as_enum();
+            egui::ComboBox::from_label("Shading model of the selected object")
                 .selected_text(format!("{current:?}"))
                 .show_ui(ui, |ui| {
-                    ui.selectable_value(current, config::ShadingModelEnum::Phong, "Phong");
-                    ui.selectable_value(current, config::ShadingModelEnum::Flat, "Flat");
-                });
-            if *current != self.shader_conf.active_model.lock().unwrap().as_enum() {
-                self.shader_conf.active_model = match current {
-                    config::ShadingModelEnum::Phong => {
-                        Arc::new(Mutex::new(crate::config::phong::Phong::new()))
-                    }
-                    config::ShadingModelEnum::Flat => {
-                        Arc::new(Mutex::new(crate::config::flat::Flat::new()))
+                    for model in config::ShadingModelEnum::ALL {
+                        ui.selectable_value(&mut current, model, format!("{model:?}"));
                     }
+                });
+            if current != object.shading_model.lock().unwrap().as_enum() {
+                object.shading_model = current.create();
+                if let Some(rs) = frame.wgpu_render_state() {
+                    renderer::set_object_shading_model(
+                        rs,
+                        object.id,
+                        &*object.shading_model.lock().unwrap(),
+                    );
                 }
             }
-            self.shader_conf
-                .active_model
-                .lock()
-                .unwrap()
-                .build_widget(ui);
+            object.shading_model.lock().unwrap().build_widget(ui);
+
+            egui::ComboBox::from_label("View mode")
+                .selected_text(self.shader_conf.view_mode.to_string())
+                .show_ui(ui, |ui| {
+                    for mode in config::ViewMode::ALL {
+                        ui.selectable_value(
+                            &mut self.shader_conf.view_mode,
+                            mode,
+                            mode.to_string(),
+                        );
+                    }
+                });
+
+            egui::CollapsingHeader::new("Camera").show(ui, |ui| {
+                let mut camera_mode = self.camera.mode();
+                egui::ComboBox::from_label("Camera mode")
+                    .selected_text(format!("{camera_mode:?}"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut camera_mode, camera::CameraMode::Fly, "Fly")
+                            .on_hover_text(
+                                "WASD to move, scroll to change speed, \
+                                 Ctrl to sprint, Alt to slow down",
+                            );
+                        ui.selectable_value(&mut camera_mode, camera::CameraMode::Orbit, "Orbit")
+                            .on_hover_text(
+                                "Drag to orbit, middle-drag to pan, scroll to dolly, \
+                                 double-click the mesh to set the centre",
+                            );
+                    });
+                self.camera.set_mode(camera_mode);
+                let object = &self.objects[self.selected];
+                if ui.button("Frame model").clicked()
+                    && let Some(bounds) = &object.bounds
+                {
+                    self.camera
+                        .frame(&bounds.transformed(object.transform.matrix()));
+                }
+                self.camera.build_widget(ui);
+            });
+            egui::CollapsingHeader::new("Camera bookmarks").show(ui, |ui| {
+                self.bookmarks.build_widget(ui, &mut self.camera);
+            });
+
+            egui::CollapsingHeader::new("Pipeline settings").show(ui, |ui| {
+                let features = frame
+                    .wgpu_render_state()
+                    .map(|rs| rs.device.features())
+                    .unwrap_or_default();
+                if self.shader_conf.pipeline.build_widget(ui, features)
+                    && let Some(rs) = frame.wgpu_render_state()
+                {
+                    renderer::rebuild_pipeline(rs, &self.shader_conf.pipeline);
+                }
+            });
 
             ui.add_space(20.0);
             ui.label("Active post processing effects");
@@ -117,23 +287,77 @@ impl eframe::App for App {
                     egui::ScrollArea::vertical()
                         .auto_shrink([false; 2])
                         .stick_to_bottom(false)
-                        .max_height(100.0)
+                        .max_height(300.0)
                         .show(ui, |ui| {
-                            self.shader_conf.active_post_effects.retain(|effect| {
-                                let mut retain = true;
-                                ui.horizontal(|ui| {
-                                    ui.label(effect.lock().unwrap().as_enum().to_string());
+                            let mut remove = None;
+                            let mut duplicate = None;
+                            let mut reorder = None;
+                            for (index, entry) in
+                                self.shader_conf.active_post_effects.iter_mut().enumerate()
+                            {
+                                let row = ui.horizontal(|ui| {
+                                    ui.dnd_drag_source(
+                                        egui::Id::new(("post_effect_handle", index)),
+                                        index,
+                                        |ui| ui.label("☰"),
+                                    )
+                                    .response
+                                    .on_hover_text("Drag to reorder");
+                                    ui.checkbox(&mut entry.enabled, "")
+                                        .on_hover_text("Enable pass");
+                                    let mut effect = entry.effect.lock().unwrap();
+                                    // Keyed by the effect itself so open state follows it around.
+                                    let id = Arc::as_ptr(&entry.effect) as *const () as usize;
+                                    egui::CollapsingHeader::new(effect.as_enum().to_string())
+                                        .id_salt(id)
+                                        .show(ui, |ui| {
+                                            ui.label(format!("mix: {}", entry.mix));
+                                            ui.add(egui::Slider::new(&mut entry.mix, 0.0..=1.0));
+                                            effect.build_widget(ui);
+                                        });
                                     ui.with_layout(
                                         egui::Layout::left_to_right(egui::Align::LEFT),
                                         |ui| {
+                                            if ui.button("[D]").on_hover_text("Duplicate").clicked()
+                                            {
+                                                duplicate = Some(index);
+                                            }
                                             if ui.button("[X]").clicked() {
-                                                retain = false;
+                                                remove = Some(index);
                                             }
                                         },
                                     )
                                 });
-                                retain
-                            });
+                                let row = row.response;
+                                if let Some(from) = row.dnd_hover_payload::<usize>()
+                                    && *from != index
+                                {
+                                    let y = if *from < index {
+                                        row.rect.bottom()
+                                    } else {
+                                        row.rect.top()
+                                    };
+                                    ui.painter().hline(
+                                        row.rect.x_range(),
+                                        y,
+                                        ui.visuals().selection.stroke,
+                                    );
+                                }
+                                if let Some(from) = row.dnd_release_payload::<usize>() {
+                                    reorder = Some((*from, index));
+                                }
+                            }
+                            let effects = &mut self.shader_conf.active_post_effects;
+                            if let Some((from, to)) = reorder {
+                                let entry = effects.remove(from);
+                                effects.insert(to, entry);
+                            }
+                            if let Some(index) = duplicate {
+                                effects.insert(index + 1, effects[index].duplicate());
+                            }
+                            if let Some(index) = remove {
+                                effects.remove(index);
+                            }
                         });
                 });
                 ui.horizontal(|ui| {
@@ -144,65 +368,154 @@ impl eframe::App for App {
                     egui::ComboBox::from_label("Add post processing effect!")
                         .selected_text(format!("{current:?}"))
                         .show_ui(ui, |ui| {
-                            ui.selectable_value(
-                                current,
-                                config::PostEffectEnum::Negative,
-                                "Negative",
-                            );
-                            ui.selectable_value(
-                                current,
-                                config::PostEffectEnum::ChromaticAberration,
-                                "ChromaticAberration",
-                            );
-                            ui.selectable_value(current, config::PostEffectEnum::Blur, "Blur");
+                            for effect in config::PostEffectEnum::ALL {
+                                ui.selectable_value(current, effect, effect.to_string());
+                            }
                         });
                     let active = &self.shader_conf.selected_effect;
                     if active.is_none() || current != &active.unwrap() {
                         self.shader_conf.selected_effect = Some(*current);
                     }
                     if ui.button("[+]").clicked() {
-                        self.shader_conf.active_post_effects.push(match current {
-                            config::PostEffectEnum::Negative => {
-                                Arc::new(Mutex::new(crate::config::negative::Negative::new()))
-                            }
-                            config::PostEffectEnum::ChromaticAberration => Arc::new(Mutex::new(
-                                crate::config::chromatic::ChromaticAberration::new(),
-                            )),
-                            config::PostEffectEnum::Blur => {
-                                Arc::new(Mutex::new(crate::config::blur::Blur::new()))
-                            }
-                        });
+                        self.shader_conf
+                            .active_post_effects
+                            .push(config::PostEffectEntry::new(current.create()));
                     }
                 });
             });
-            if self.object.build_widget(ui, ctx) {
-                if let Some(rs) = frame.wgpu_render_state() {
-                    object::Object::update_obj(rs, &self.object.opened_file.as_deref());
+            let object = &mut self.objects[self.selected];
+            if let Some(edit) = object.build_widget(ui, ctx)
+                && let Some(rs) = frame.wgpu_render_state()
+            {
+                let bounds = object.load(rs);
+                // Tweaking a primitive keeps the view, so the change can be watched.
+                if edit == object::MeshEdit::Replaced {
+                    self.camera
+                        .frame(&bounds.transformed(object.transform.matrix()));
                 }
             };
+            // Files are read in the background and replace their object's mesh once
+            // they're ready.
+            if let Some(rs) = frame.wgpu_render_state() {
+                for (index, object) in self.objects.iter_mut().enumerate() {
+                    // A reloaded file keeps the view, like a tweaked primitive.
+                    if object.poll_loading(rs) == Some(object::MeshEdit::Replaced)
+                        && index == self.selected
+                        && let Some(bounds) = &object.bounds
+                    {
+                        self.camera
+                            .frame(&bounds.transformed(object.transform.matrix()));
+                    }
+                }
+            }
+            ui.horizontal(|ui| {
+                ui.label("gizmo:");
+                for mode in gizmo::GizmoMode::ALL {
+                    ui.selectable_value(&mut self.gizmo.mode, mode, mode.to_string());
+                }
+            });
+            ui.checkbox(&mut self.texture_viewer.open, "Texture viewer");
         });
+        self.texture_viewer
+            .show(ctx, &self.shader_conf.active_post_effects);
         ctx.request_repaint();
     }
 }
 
 impl App {
     fn custom_painting(&mut self, ui: &mut egui::Ui) {
-        let (rect, _response) = ui.allocate_exact_size(ui.available_size(), egui::Sense::drag());
-
-        ui.painter().add(egui_wgpu::Callback::new_paint_callback(
-            rect,
-            renderer::ObjectRenderCallback {
-                view_projection: renderer::CameraUniform::from_camera(&self.camera),
-                light: self.light,
-                shading_model: self.shader_conf.active_model.clone(),
-                post_effects: self.shader_conf.active_post_effects.clone(),
-            },
-        ));
+        let (rect, response) =
+            ui.allocate_exact_size(ui.available_size(), egui::Sense::click_and_drag());
+        let (time, delta_time) = ui.input(|i| (i.time, i.stable_dt));
+        let frame = ui.ctx().cumulative_pass_nr();
+
+        let taa_active = self.shader_conf.active_post_effects.iter().any(|entry| {
+            entry.enabled && entry.effect.lock().unwrap().as_enum() == config::PostEffectEnum::Taa
+        });
+        if taa_active {
+            let pixels = rect.size() * ui.ctx().pixels_per_point();
+            self.camera
+                .set_jitter(frame, glam::vec2(pixels.x, pixels.y));
+        } else {
+            self.camera.clear_jitter();
+        }
+
+        // Double-clicking the mesh in orbit mode moves the orbit centre there.
+        let pick = if self.camera.mode() == camera::CameraMode::Orbit
+            && response.double_clicked()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let pixel = (pos - rect.min) * ui.ctx().pixels_per_point();
+            Some([pixel.x.max(0.0) as u32, pixel.y.max(0.0) as u32])
+        } else {
+            None
+        };
+
+        // The gizmo draws over the scene but moves the object before it's drawn.
+        let scene_shape = ui.painter().add(egui::Shape::Noop);
+        let view_projection = renderer::CameraUniform::from_camera(&self.camera);
+        self.gizmo.show(
+            ui,
+            &response,
+            &view_projection,
+            &mut self.objects[self.selected].transform,
+        );
+        let objects = self
+            .objects
+            .iter()
+            .filter(|object| object.visible)
+            .map(|object| renderer::ObjectDraw {
+                id: object.id,
+                model: renderer::ModelUniform::new(object.transform.matrix()),
+                shading_model: object.shading_model.clone(),
+            })
+            .collect();
+
+        ui.painter().set(
+            scene_shape,
+            egui_wgpu::Callback::new_paint_callback(
+                rect,
+                renderer::ObjectRenderCallback {
+                    view_projection,
+                    objects,
+                    light: self.light,
+                    post_effects: self.shader_conf.active_post_effects.clone(),
+                    frame: renderer::FrameUniform::new(time as f32, delta_time, frame as u32),
+                    inspect: self.texture_viewer.request(),
+                    pick,
+                    view_mode: self.shader_conf.view_mode,
+                    wireframe: self.shader_conf.pipeline.wireframe.then(|| {
+                        renderer::WireframeUniform::new(
+                            self.shader_conf.pipeline.wireframe_color,
+                            self.shader_conf.pipeline.wireframe_width,
+                        )
+                    }),
+                },
+            ),
+        );
     }
 }
 
 fn main() {
-    let nativeoptions = eframe::NativeOptions::default();
+    let setup = egui_wgpu::WgpuSetupCreateNew::default();
+    let default_descriptor = setup.device_descriptor.clone();
+    let nativeoptions = eframe::NativeOptions {
+        wgpu_options: egui_wgpu::WgpuConfiguration {
+            wgpu_setup: egui_wgpu::WgpuSetup::CreateNew(egui_wgpu::WgpuSetupCreateNew {
+                device_descriptor: Arc::new(move |adapter| {
+                    let mut descriptor = default_descriptor(adapter);
+                    // Line and point polygon modes, where the adapter has them.
+                    descriptor.required_features |= adapter.features()
+                        & (egui_wgpu::wgpu::Features::POLYGON_MODE_LINE
+                            | egui_wgpu::wgpu::Features::POLYGON_MODE_POINT);
+                    descriptor
+                }),
+                ..setup
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
     eframe::run_native(
         "egui wgpu demo",
         nativeoptions,
@@ -0,0 +1,332 @@
+//! Triangle meshes built on the CPU, before they become vertex buffers.
+
+mod gltf;
+mod obj;
+mod ply;
+mod polygon;
+pub mod primitive;
+mod stl;
+mod teapot;
+
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use eframe::egui_wgpu::wgpu::{self, util::DeviceExt as _};
+
+use crate::{object::Bounds, renderer::Vertex};
+
+/// An indexed triangle mesh with counter-clockwise front faces.
+#[derive(Default)]
+pub struct Mesh {
+    pub positions: Vec<glam::Vec3>,
+    pub normals: Vec<glam::Vec3>,
+    pub texcoords: Vec<glam::Vec2>,
+    /// Direction of increasing u, with the sign of the bitangent in w. Filled in by
+    /// [`Mesh::compute_tangents`].
+    pub tangents: Vec<glam::Vec4>,
+    /// Linear RGBA, or empty when the mesh has no vertex colours, which reads as white.
+    pub colors: Vec<glam::Vec4>,
+    pub indices: Vec<u32>,
+    /// For each triangle, which of its edges are edges of the polygon it was cut from,
+    /// as [`Mesh::add_polygon`] records them. Empty when every triangle stands alone.
+    pub polygon_edges: Vec<u32>,
+}
+
+impl Mesh {
+    /// Adds `other`'s triangles to this mesh.
+    pub fn append(&mut self, other: Mesh) {
+        let offset = self.positions.len() as u32;
+        self.positions.extend(other.positions);
+        self.normals.extend(other.normals);
+        self.texcoords.extend(other.texcoords);
+        self.tangents.extend(other.tangents);
+        if !self.colors.is_empty() || !other.colors.is_empty() {
+            self.colors.resize(offset as usize, glam::Vec4::ONE);
+            self.colors.extend(other.colors);
+            self.colors.resize(self.positions.len(), glam::Vec4::ONE);
+        }
+        if !self.polygon_edges.is_empty() || !other.polygon_edges.is_empty() {
+            self.polygon_edges.resize(self.indices.len() / 3, 0b111);
+            self.polygon_edges.extend(other.polygon_edges);
+            self.polygon_edges
+                .resize((self.indices.len() + other.indices.len()) / 3, 0b111);
+        }
+        self.indices
+            .extend(other.indices.into_iter().map(|i| i + offset));
+    }
+
+    /// Adds the polygon through `corners`, split into triangles by ear clipping. Its
+    /// diagonals are left out of the wireframe.
+    pub fn add_polygon(&mut self, corners: &[u32]) {
+        self.polygon_edges.resize(self.indices.len() / 3, 0b111);
+        for (triangle, edges) in polygon::triangulate(&self.positions, corners) {
+            self.indices.extend(triangle);
+            self.polygon_edges.push(edges);
+        }
+    }
+
+    /// Gives every triangle its own vertices, with the triangle's normal.
+    pub fn compute_flat_normals(&mut self) {
+        let mut mesh = Mesh {
+            polygon_edges: std::mem::take(&mut self.polygon_edges),
+            ..Default::default()
+        };
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+            let normal = (self.positions[b] - self.positions[a])
+                .cross(self.positions[c] - self.positions[a])
+                .normalize_or(glam::Vec3::Z);
+            for i in [a, b, c] {
+                mesh.indices.push(mesh.positions.len() as u32);
+                mesh.positions.push(self.positions[i]);
+                mesh.normals.push(normal);
+                mesh.texcoords.push(self.texcoords[i]);
+                if let Some(color) = self.colors.get(i) {
+                    mesh.colors.push(*color);
+                }
+            }
+        }
+        *self = mesh;
+    }
+
+    /// Normals averaged over the triangles sharing each vertex, weighted by their area.
+    pub fn compute_smooth_normals(&mut self) {
+        let mut normals = vec![glam::Vec3::ZERO; self.positions.len()];
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+            let normal = (self.positions[b] - self.positions[a])
+                .cross(self.positions[c] - self.positions[a]);
+            for i in [a, b, c] {
+                normals[i] += normal;
+            }
+        }
+        self.normals = normals
+            .into_iter()
+            .map(|normal| normal.normalize_or(glam::Vec3::Z))
+            .collect();
+    }
+
+    /// Tangents from the texture coordinates, averaged over the triangles sharing a
+    /// vertex and made perpendicular to its normal.
+    pub fn compute_tangents(&mut self) {
+        let mut tangents = vec![glam::Vec3::ZERO; self.positions.len()];
+        let mut bitangents = vec![glam::Vec3::ZERO; self.positions.len()];
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+            let (edge1, edge2) = (
+                self.positions[b] - self.positions[a],
+                self.positions[c] - self.positions[a],
+            );
+            let (duv1, duv2) = (
+                self.texcoords[b] - self.texcoords[a],
+                self.texcoords[c] - self.texcoords[a],
+            );
+            let det = duv1.perp_dot(duv2);
+            if det.abs() < 1e-12 {
+                continue;
+            }
+            let tangent = (edge1 * duv2.y - edge2 * duv1.y) / det;
+            let bitangent = (edge2 * duv1.x - edge1 * duv2.x) / det;
+            for i in [a, b, c] {
+                tangents[i] += tangent;
+                bitangents[i] += bitangent;
+            }
+        }
+
+        self.tangents = (0..self.positions.len())
+            .map(|i| {
+                let normal = self.normals[i];
+                let tangent = (tangents[i] - normal * normal.dot(tangents[i]))
+                    .try_normalize()
+                    .unwrap_or_else(|| normal.any_orthonormal_vector());
+                let handedness = if normal.cross(tangent).dot(bitangents[i]) < 0.0 {
+                    -1.0
+                } else {
+                    1.0
+                };
+                tangent.extend(handedness)
+            })
+            .collect();
+    }
+
+    /// One vertex per index, as the scene pass draws without an index buffer.
+    pub fn vertices(&self) -> Vec<Vertex> {
+        self.indices
+            .iter()
+            .enumerate()
+            .map(|(corner, &i)| {
+                let i = i as usize;
+                Vertex::new(
+                    self.positions[i].to_array(),
+                    self.normals[i].to_array(),
+                    self.texcoords[i].to_array(),
+                    self.tangents
+                        .get(i)
+                        .copied()
+                        .unwrap_or(glam::Vec4::X)
+                        .to_array(),
+                    self.colors
+                        .get(i)
+                        .copied()
+                        .unwrap_or(glam::Vec4::ONE)
+                        .to_array(),
+                    self.polygon_edges.get(corner / 3).copied().unwrap_or(0b111),
+                )
+            })
+            .collect()
+    }
+}
+
+/// A texture view with the sampler it's read through.
+pub type SampledTexture = (wgpu::TextureView, wgpu::Sampler);
+
+/// The vertex range of one part of an uploaded [`Model`], with its texture.
+pub type UploadedPart = (Range<u32>, Option<SampledTexture>);
+
+/// How a part of a [`Model`] is textured.
+pub struct Material {
+    /// Drawn with the renderer's proxy texture when there's none.
+    pub texture: Option<image::RgbaImage>,
+    /// Wrapping of the texture along u and v.
+    pub address_mode: [wgpu::AddressMode; 2],
+    /// PBR metallic and roughness factors, for shading models that can use them.
+    pub metallic_roughness: Option<(f32, f32)>,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            texture: None,
+            address_mode: [wgpu::AddressMode::ClampToEdge; 2],
+            metallic_roughness: None,
+        }
+    }
+}
+
+impl Material {
+    /// The texture and its sampler, if the material has a texture.
+    pub fn upload_texture(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Option<SampledTexture> {
+        let rgba = self.texture.as_ref()?;
+        let size = wgpu::Extent3d {
+            width: rgba.width(),
+            height: rgba.height(),
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            rgba,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * rgba.width()),
+                rows_per_image: Some(rgba.height()),
+            },
+            size,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: self.address_mode[0],
+            address_mode_v: self.address_mode[1],
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        Some((view, sampler))
+    }
+}
+
+/// Everything read from a model file: one mesh per material.
+#[derive(Default)]
+pub struct Model {
+    pub parts: Vec<(Mesh, Material)>,
+    /// The model file and the material, texture and buffer files it refers to.
+    pub files: Vec<PathBuf>,
+}
+
+/// An sRGB encoded colour, as files store them, in the linear space shaders blend in.
+pub fn srgb_to_linear(color: glam::Vec4) -> glam::Vec4 {
+    color.truncate().powf(2.2).extend(color.w)
+}
+
+impl From<Mesh> for Model {
+    fn from(mesh: Mesh) -> Self {
+        Model {
+            parts: vec![(mesh, Material::default())],
+            files: Vec::new(),
+        }
+    }
+}
+
+impl Model {
+    /// Reads an OBJ, glTF, GLB, STL or PLY file, picked by its extension. Errors say
+    /// what was wrong with the file, for the user.
+    pub fn load(path: &Path) -> Result<Model, String> {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_ascii_lowercase());
+        let mut model = match extension.as_ref().and_then(|extension| extension.to_str()) {
+            Some("gltf" | "glb") => gltf::load(path),
+            Some("stl") => stl::load(path),
+            Some("ply") => ply::load(path),
+            _ => obj::load(path),
+        }?;
+        model.files.insert(0, path.to_path_buf());
+        Ok(model)
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        let positions: Vec<f32> = self
+            .parts
+            .iter()
+            .flat_map(|(mesh, _)| mesh.positions.iter().flat_map(|p| p.to_array()))
+            .collect();
+        Bounds::from_positions(&positions)
+    }
+
+    /// One vertex buffer for all the parts, with the range of vertices and the texture
+    /// of each.
+    pub fn upload(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> (wgpu::Buffer, Vec<UploadedPart>) {
+        let mut vertices = Vec::new();
+        let mut parts = Vec::new();
+        for (mesh, material) in &self.parts {
+            let start = vertices.len() as u32;
+            vertices.extend(mesh.vertices());
+            parts.push((
+                start..vertices.len() as u32,
+                material.upload_texture(device, queue),
+            ));
+        }
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        (vertex_buffer, parts)
+    }
+}
@@ -0,0 +1,242 @@
+//! glTF 2.0 files, either `.gltf` with embedded or external buffers and images, or
+//! binary `.glb`.
+
+use std::path::Path;
+
+use ::gltf::{image::Format, mesh::Mode, texture::WrappingMode};
+use eframe::egui_wgpu::wgpu;
+
+use super::{Material, Mesh, Model};
+
+/// The meshes of the default scene, placed by their nodes' transforms and merged into
+/// one part per material.
+pub fn load(path: &Path) -> Result<Model, String> {
+    let (document, buffers, images) =
+        ::gltf::import(path).map_err(|e| format!("Failed to load glTF file: {e}"))?;
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or("glTF file has no scene")?;
+
+    let mut parts: Vec<(Option<usize>, Mesh, Material)> = Vec::new();
+    let mut nodes: Vec<_> = scene
+        .nodes()
+        .map(|node| (node, glam::Mat4::IDENTITY))
+        .collect();
+    while let Some((node, parent)) = nodes.pop() {
+        let transform = parent * glam::Mat4::from_cols_array_2d(&node.transform().matrix());
+        for primitive in node.mesh().iter().flat_map(|mesh| mesh.primitives()) {
+            let Some(mesh) = read_primitive(&primitive, &buffers, transform) else {
+                continue;
+            };
+            let material = primitive.material();
+            match parts
+                .iter_mut()
+                .find(|(index, ..)| *index == material.index())
+            {
+                Some((_, part, _)) => part.append(mesh),
+                None => parts.push((material.index(), mesh, read_material(&material, &images)?)),
+            }
+        }
+        nodes.extend(node.children().map(|child| (child, transform)));
+    }
+
+    // Buffers and images in other files, as opposed to embedded ones.
+    let directory = path.parent().unwrap_or(Path::new(""));
+    let uris = document
+        .buffers()
+        .filter_map(|buffer| match buffer.source() {
+            ::gltf::buffer::Source::Uri(uri) => Some(uri),
+            ::gltf::buffer::Source::Bin => None,
+        })
+        .chain(document.images().filter_map(|image| match image.source() {
+            ::gltf::image::Source::Uri { uri, .. } => Some(uri),
+            ::gltf::image::Source::View { .. } => None,
+        }));
+    let files = uris
+        .filter(|uri| !uri.starts_with("data:"))
+        .map(|uri| directory.join(uri))
+        .collect();
+
+    Ok(Model {
+        parts: parts
+            .into_iter()
+            .map(|(_, mesh, material)| (mesh, material))
+            .collect(),
+        files,
+    })
+}
+
+/// The primitive's triangles in scene space, or `None` for points and lines.
+fn read_primitive(
+    primitive: &::gltf::Primitive,
+    buffers: &[::gltf::buffer::Data],
+    transform: glam::Mat4,
+) -> Option<Mesh> {
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+    let positions: Vec<glam::Vec3> = reader
+        .read_positions()?
+        .map(|p| transform.transform_point3(p.into()))
+        .collect();
+    let indices: Vec<u32> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect(),
+        None => (0..positions.len() as u32).collect(),
+    };
+    let mut indices: Vec<u32> = match primitive.mode() {
+        Mode::Triangles => indices,
+        Mode::TriangleStrip => (0..indices.len().saturating_sub(2))
+            .flat_map(|i| {
+                let odd = i % 2;
+                [indices[i], indices[i + 1 + odd], indices[i + 2 - odd]]
+            })
+            .collect(),
+        Mode::TriangleFan => (1..indices.len().saturating_sub(1))
+            .flat_map(|i| [indices[i], indices[i + 1], indices[0]])
+            .collect(),
+        Mode::Points | Mode::Lines | Mode::LineLoop | Mode::LineStrip => return None,
+    };
+    // A mirroring transform turns the triangles inside out.
+    let mirrored = transform.determinant() < 0.0;
+    if mirrored {
+        for triangle in indices.chunks_exact_mut(3) {
+            triangle.swap(1, 2);
+        }
+    }
+
+    let set = primitive
+        .material()
+        .pbr_metallic_roughness()
+        .base_color_texture()
+        .map_or(0, |info| info.tex_coord());
+    let texcoords = match reader.read_tex_coords(set) {
+        Some(texcoords) => texcoords.into_f32().map(glam::Vec2::from).collect(),
+        None => vec![glam::Vec2::ZERO; positions.len()],
+    };
+
+    // Unlike texels, vertex colours are linear already.
+    let colors = reader
+        .read_colors(0)
+        .map(|colors| colors.into_rgba_f32().map(glam::Vec4::from).collect())
+        .unwrap_or_default();
+
+    let mut mesh = Mesh {
+        positions,
+        texcoords,
+        colors,
+        indices,
+        ..Default::default()
+    };
+    let linear = glam::Mat3::from_mat4(transform);
+    let Some(normals) = reader.read_normals() else {
+        // Without normals the spec asks for flat shading, and ignores the tangents.
+        mesh.compute_flat_normals();
+        mesh.compute_tangents();
+        return Some(mesh);
+    };
+    let normal_matrix = linear.inverse().transpose();
+    mesh.normals = normals
+        .map(|n| (normal_matrix * glam::Vec3::from(n)).normalize_or(glam::Vec3::Z))
+        .collect();
+    match reader.read_tangents() {
+        Some(tangents) => {
+            let handedness = if mirrored { -1.0 } else { 1.0 };
+            mesh.tangents = tangents
+                .map(|[x, y, z, w]| {
+                    (linear * glam::vec3(x, y, z))
+                        .normalize_or(glam::Vec3::X)
+                        .extend(w * handedness)
+                })
+                .collect();
+        }
+        None => mesh.compute_tangents(),
+    }
+    Some(mesh)
+}
+
+/// The base colour texture with the base colour factor baked in, or a one texel texture
+/// of the factor when there's no texture. Metallic and roughness are only the factors:
+/// shading models take one value of each per object, so the metallic-roughness texture
+/// isn't read.
+fn read_material(
+    material: &::gltf::Material,
+    images: &[::gltf::image::Data],
+) -> Result<Material, String> {
+    let pbr = material.pbr_metallic_roughness();
+    let info = pbr.base_color_texture();
+    let mut texture = match &info {
+        Some(info) => to_rgba8(&images[info.texture().source().index()])?,
+        None => image::RgbaImage::from_pixel(1, 1, image::Rgba([255; 4])),
+    };
+
+    let factor = glam::Vec4::from(pbr.base_color_factor());
+    if factor != glam::Vec4::ONE {
+        // The texture is sRGB encoded; scaling an encoded value by f^(1/2.2) scales the
+        // linear colour by f.
+        let scale = factor
+            .truncate()
+            .powf(1.0 / 2.2)
+            .extend(factor.w)
+            .to_array();
+        for pixel in texture.pixels_mut() {
+            for (channel, scale) in pixel.0.iter_mut().zip(scale) {
+                *channel = (*channel as f32 * scale).round() as u8;
+            }
+        }
+    }
+
+    let address_mode = |mode| match mode {
+        WrappingMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+        WrappingMode::MirroredRepeat => wgpu::AddressMode::MirrorRepeat,
+        WrappingMode::Repeat => wgpu::AddressMode::Repeat,
+    };
+    let sampler = info.map(|info| info.texture().sampler());
+    Ok(Material {
+        texture: Some(texture),
+        address_mode: sampler.map_or([wgpu::AddressMode::Repeat; 2], |sampler| {
+            [
+                address_mode(sampler.wrap_s()),
+                address_mode(sampler.wrap_t()),
+            ]
+        }),
+        metallic_roughness: Some((pbr.metallic_factor(), pbr.roughness_factor())),
+    })
+}
+
+/// Decoded image data in any of glTF's pixel formats, as 8 bit RGBA.
+fn to_rgba8(data: &::gltf::image::Data) -> Result<image::RgbaImage, String> {
+    let (channels, bytes) = match data.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 => (3, 1),
+        Format::R8G8B8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+        Format::R32G32B32FLOAT => (3, 4),
+        Format::R32G32B32A32FLOAT => (4, 4),
+    };
+    let pixels = data
+        .pixels
+        .chunks_exact(channels * bytes)
+        .flat_map(|texel| {
+            let channel = |c: usize| match bytes {
+                1 => texel[c],
+                // Little endian, so the high byte comes second.
+                2 => texel[c * 2 + 1],
+                _ => {
+                    let value = f32::from_le_bytes(texel[c * 4..c * 4 + 4].try_into().unwrap());
+                    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+                }
+            };
+            match channels {
+                1 => [channel(0), channel(0), channel(0), 255],
+                2 => [channel(0), channel(0), channel(0), channel(1)],
+                3 => [channel(0), channel(1), channel(2), 255],
+                _ => [channel(0), channel(1), channel(2), channel(3)],
+            }
+        })
+        .collect();
+    image::RgbaImage::from_raw(data.width, data.height, pixels)
+        .ok_or_else(|| "glTF image data doesn't match its size".to_string())
+}
@@ -0,0 +1,96 @@
+//! Wavefront OBJ files, through tobj.
+
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use super::{Material, Mesh, Model, srgb_to_linear};
+
+/// The first model of the file, textured with the first material's diffuse texture.
+/// MTL and texture paths are relative to the file. Faces with more than three corners
+/// are triangulated here rather than by tobj, whose fans break concave polygons.
+pub fn load(path: &Path) -> Result<Model, String> {
+    let directory = path.parent().unwrap_or(Path::new(""));
+    // tobj's own `load_obj`, noting the MTL files it reads.
+    let files = RefCell::new(Vec::<PathBuf>::new());
+    let file = File::open(path).map_err(|e| format!("Failed to open OBJ file: {e}"))?;
+    let (models, obj_materials) = tobj::load_obj_buf(
+        &mut BufReader::new(file),
+        &tobj::LoadOptions {
+            triangulate: false,
+            single_index: true,
+            ..Default::default()
+        },
+        |mtl| {
+            let mtl = directory.join(mtl);
+            files.borrow_mut().push(mtl.clone());
+            tobj::load_mtl(mtl)
+        },
+    )
+    .map_err(|e| format!("Failed to load OBJ file: {e}"))?;
+    let mut files = files.into_inner();
+
+    let mut material = Material::default();
+    if let Ok(materials) = obj_materials
+        && let Some(mat) = materials.first()
+        && let Some(texture) = &mat.diffuse_texture
+    {
+        let texture = directory.join(texture);
+        let image = image::open(&texture)
+            .map_err(|e| format!("Failed to open texture {}: {e}", texture.display()))?;
+        material.texture = Some(image.to_rgba8());
+        files.push(texture);
+    }
+
+    let obj_mesh = &models.first().ok_or("OBJ file has no objects")?.mesh;
+    let vec3s = |values: &[f32]| -> Vec<glam::Vec3> {
+        values.chunks_exact(3).map(glam::Vec3::from_slice).collect()
+    };
+    let vertex_count = obj_mesh.positions.len() / 3;
+    let mut mesh = Mesh {
+        positions: vec3s(&obj_mesh.positions),
+        normals: if obj_mesh.normals.is_empty() {
+            vec![glam::Vec3::Z; vertex_count]
+        } else {
+            vec3s(&obj_mesh.normals)
+        },
+        texcoords: if obj_mesh.texcoords.is_empty() {
+            vec![glam::Vec2::ZERO; vertex_count]
+        } else {
+            obj_mesh
+                .texcoords
+                .chunks_exact(2)
+                .map(glam::Vec2::from_slice)
+                .collect()
+        },
+        tangents: Vec::new(),
+        // Colours from `v x y z r g b` lines.
+        colors: obj_mesh
+            .vertex_color
+            .chunks_exact(3)
+            .map(|rgb| srgb_to_linear(glam::Vec3::from_slice(rgb).extend(1.0)))
+            .collect(),
+        indices: Vec::new(),
+        polygon_edges: Vec::new(),
+    };
+    // Without face arities every face is a triangle.
+    let arities = match obj_mesh.face_arities.as_slice() {
+        [] => &vec![3; obj_mesh.indices.len() / 3],
+        arities => arities,
+    };
+    let mut start = 0;
+    for &arity in arities {
+        let end = start + arity as usize;
+        mesh.add_polygon(&obj_mesh.indices[start..end]);
+        start = end;
+    }
+    mesh.compute_tangents();
+
+    Ok(Model {
+        parts: vec![(mesh, material)],
+        files,
+    })
+}
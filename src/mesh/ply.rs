@@ -0,0 +1,454 @@
+//! PLY files, ASCII or binary, as written by scanners and point cloud tools.
+
+use std::{io, path::Path};
+
+use super::{Mesh, Model, srgb_to_linear};
+
+/// The vertices and faces of the file, with its vertex colours. Normals are averaged
+/// from the faces when the file has none, and polygons are split by ear clipping.
+pub fn load(path: &Path) -> Result<Model, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to open PLY file: {e}"))?;
+    let mesh = parse(&bytes).map_err(|e| format!("Failed to load PLY file: {e}"))?;
+    Ok(Model::from(mesh))
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> io::Result<Scalar> {
+        Ok(match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            _ => return Err(invalid(format!("unknown PLY type `{name}`"))),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+
+    /// The value colour channels of this type reach at full intensity.
+    fn full_intensity(self) -> f64 {
+        match self {
+            Scalar::I8 => i8::MAX as f64,
+            Scalar::U8 => u8::MAX as f64,
+            Scalar::I16 => i16::MAX as f64,
+            Scalar::U16 => u16::MAX as f64,
+            Scalar::I32 => i32::MAX as f64,
+            Scalar::U32 => u32::MAX as f64,
+            Scalar::F32 | Scalar::F64 => 1.0,
+        }
+    }
+}
+
+struct Property {
+    name: String,
+    /// Type of the length prefix, for list properties.
+    list: Option<Scalar>,
+    scalar: Scalar,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// Reads the body one value at a time, whatever its format.
+struct Body<'a> {
+    format: Format,
+    bytes: &'a [u8],
+    tokens: std::str::SplitAsciiWhitespace<'a>,
+}
+
+impl Body<'_> {
+    fn read(&mut self, scalar: Scalar) -> io::Result<f64> {
+        if self.format == Format::Ascii {
+            let token = self
+                .tokens
+                .next()
+                .ok_or_else(|| invalid("PLY file ends early"))?;
+            return token
+                .parse()
+                .map_err(|_| invalid(format!("`{token}` isn't a number")));
+        }
+
+        if self.bytes.len() < scalar.size() {
+            return Err(invalid("PLY file ends early"));
+        }
+        let (bytes, rest) = self.bytes.split_at(scalar.size());
+        self.bytes = rest;
+        // In little endian order, whatever the file's.
+        let mut value = [0; 8];
+        let value = &mut value[..bytes.len()];
+        value.copy_from_slice(bytes);
+        if self.format == Format::BinaryBigEndian {
+            value.reverse();
+        }
+        Ok(match scalar {
+            Scalar::I8 => value[0] as i8 as f64,
+            Scalar::U8 => value[0] as f64,
+            Scalar::I16 => i16::from_le_bytes([value[0], value[1]]) as f64,
+            Scalar::U16 => u16::from_le_bytes([value[0], value[1]]) as f64,
+            Scalar::I32 => i32::from_le_bytes(value[..4].try_into().unwrap()) as f64,
+            Scalar::U32 => u32::from_le_bytes(value[..4].try_into().unwrap()) as f64,
+            Scalar::F32 => f32::from_le_bytes(value[..4].try_into().unwrap()) as f64,
+            Scalar::F64 => f64::from_le_bytes(value[..8].try_into().unwrap()),
+        })
+    }
+
+    /// One property of an element: a single value, or the values of a list.
+    fn read_property(&mut self, property: &Property, values: &mut Vec<f64>) -> io::Result<()> {
+        values.clear();
+        let count = match property.list {
+            Some(scalar) => self.read(scalar)? as usize,
+            None => 1,
+        };
+        for _ in 0..count {
+            values.push(self.read(property.scalar)?);
+        }
+        Ok(())
+    }
+}
+
+/// The header's format and elements, and the body after it.
+fn parse_header(bytes: &[u8]) -> io::Result<(Format, Vec<Element>, &[u8])> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut rest = bytes;
+    loop {
+        let end = rest
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .ok_or_else(|| invalid("PLY header has no `end_header`"))?;
+        let line = std::str::from_utf8(&rest[..end])
+            .map_err(|_| invalid("PLY header isn't text"))?
+            .trim();
+        rest = &rest[end + 1..];
+
+        let words: Vec<&str> = line.split_ascii_whitespace().collect();
+        match words.as_slice() {
+            ["ply"] | ["comment", ..] | ["obj_info", ..] | [] => {}
+            ["format", name, _version] => {
+                format = Some(match *name {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => return Err(invalid(format!("unknown PLY format `{name}`"))),
+                });
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| invalid(format!("bad count for element `{name}`")))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, scalar, name] => elements
+                .last_mut()
+                .ok_or_else(|| invalid("PLY property outside an element"))?
+                .properties
+                .push(Property {
+                    name: name.to_string(),
+                    list: Some(Scalar::parse(count)?),
+                    scalar: Scalar::parse(scalar)?,
+                }),
+            ["property", scalar, name] => elements
+                .last_mut()
+                .ok_or_else(|| invalid("PLY property outside an element"))?
+                .properties
+                .push(Property {
+                    name: name.to_string(),
+                    list: None,
+                    scalar: Scalar::parse(scalar)?,
+                }),
+            ["end_header"] => break,
+            _ => return Err(invalid(format!("unexpected PLY header line `{line}`"))),
+        }
+    }
+
+    let format = format.ok_or_else(|| invalid("PLY header has no format"))?;
+    Ok((format, elements, rest))
+}
+
+fn parse(bytes: &[u8]) -> io::Result<Mesh> {
+    if !bytes.starts_with(b"ply") {
+        return Err(invalid("not a PLY file"));
+    }
+    let (format, elements, body) = parse_header(bytes)?;
+    let mut body = Body {
+        format,
+        bytes: body,
+        tokens: match format {
+            Format::Ascii => std::str::from_utf8(body)
+                .map_err(|_| invalid("ASCII PLY body isn't text"))?
+                .split_ascii_whitespace(),
+            _ => "".split_ascii_whitespace(),
+        },
+    };
+
+    let mut mesh = Mesh::default();
+    let mut has_normals = false;
+    let mut values = Vec::new();
+    for element in &elements {
+        let find = |names: &[&str]| {
+            element
+                .properties
+                .iter()
+                .position(|property| names.contains(&property.name.as_str()))
+        };
+        match element.name.as_str() {
+            "vertex" => {
+                let position = [find(&["x"]), find(&["y"]), find(&["z"])];
+                let normal = [find(&["nx"]), find(&["ny"]), find(&["nz"])];
+                let texcoord = [
+                    find(&["u", "s", "texture_u", "texture_s"]),
+                    find(&["v", "t", "texture_v", "texture_t"]),
+                ];
+                let color = [
+                    find(&["red", "diffuse_red"]),
+                    find(&["green", "diffuse_green"]),
+                    find(&["blue", "diffuse_blue"]),
+                ];
+                let alpha = find(&["alpha"]);
+                has_normals = normal.iter().all(Option::is_some);
+                let has_colors = color.iter().all(Option::is_some);
+
+                let mut vertex = vec![0.0; element.properties.len()];
+                for _ in 0..element.count {
+                    for (value, property) in vertex.iter_mut().zip(&element.properties) {
+                        body.read_property(property, &mut values)?;
+                        *value = values.first().copied().unwrap_or_default();
+                    }
+                    let get = |index: Option<usize>| index.map_or(0.0, |i| vertex[i] as f32);
+                    let channel = |index: Option<usize>| {
+                        index.map_or(1.0, |i| {
+                            (vertex[i] / element.properties[i].scalar.full_intensity()) as f32
+                        })
+                    };
+                    mesh.positions
+                        .push(glam::Vec3::from_array(position.map(get)));
+                    if has_normals {
+                        mesh.normals.push(glam::Vec3::from_array(normal.map(get)));
+                    }
+                    mesh.texcoords
+                        .push(glam::Vec2::from_array(texcoord.map(get)));
+                    if has_colors {
+                        let [r, g, b] = color.map(channel);
+                        mesh.colors
+                            .push(srgb_to_linear(glam::vec4(r, g, b, channel(alpha))));
+                    }
+                }
+            }
+            "face" => {
+                let indices = find(&["vertex_indices", "vertex_index"]);
+                for _ in 0..element.count {
+                    for (index, property) in element.properties.iter().enumerate() {
+                        body.read_property(property, &mut values)?;
+                        if Some(index) == indices {
+                            let vertex_count = mesh.positions.len() as u32;
+                            let corners: Vec<u32> = values.iter().map(|&v| v as u32).collect();
+                            if let Some(index) = corners.iter().find(|&&i| i >= vertex_count) {
+                                return Err(invalid(format!(
+                                    "face refers to vertex {index} of {vertex_count}"
+                                )));
+                            }
+                            mesh.add_polygon(&corners);
+                        }
+                    }
+                }
+            }
+            // Edges, materials and whatever else the file carries.
+            _ => {
+                for _ in 0..element.count {
+                    for property in &element.properties {
+                        body.read_property(property, &mut values)?;
+                    }
+                }
+            }
+        }
+    }
+
+    if !has_normals {
+        mesh.compute_smooth_normals();
+    }
+    mesh.compute_tangents();
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUAD: [[f32; 3]; 4] = [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0],
+    ];
+
+    fn parse_error(bytes: &[u8]) -> String {
+        match parse(bytes) {
+            Ok(_) => panic!("parsed an invalid PLY file"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    /// A quad with float positions and an int index list, in a binary format.
+    fn binary_quad(
+        format: &str,
+        to_bytes: fn(f32) -> [u8; 4],
+        index: fn(i32) -> [u8; 4],
+    ) -> Vec<u8> {
+        let mut bytes = format!(
+            "ply\nformat {format} 1.0\nelement vertex 4\nproperty float x\n\
+             property float y\nproperty float z\nelement face 1\n\
+             property list uchar int vertex_indices\nend_header\n"
+        )
+        .into_bytes();
+        for position in QUAD {
+            bytes.extend(position.into_iter().flat_map(to_bytes));
+        }
+        bytes.push(4);
+        bytes.extend([0, 1, 2, 3].into_iter().flat_map(index));
+        bytes
+    }
+
+    fn assert_quad(mesh: &Mesh) {
+        assert_eq!(mesh.positions, QUAD.map(glam::Vec3::from_array));
+        assert_eq!(mesh.indices.len(), 6);
+        assert_eq!(mesh.normals, vec![glam::Vec3::Z; 4]);
+    }
+
+    #[test]
+    fn parses_ascii() {
+        let src = "ply\nformat ascii 1.0\ncomment made by hand\nelement vertex 3\n\
+                   property float x\nproperty float y\nproperty float z\n\
+                   property float nx\nproperty float ny\nproperty float nz\n\
+                   property float u\nproperty float v\n\
+                   element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+                   0 0 0 0 0 1 0 0\n1 0 0 0 0 1 1 0\n0 1 0 0 0 1 0 1\n3 0 1 2\n";
+        let mesh = parse(src.as_bytes()).unwrap();
+        assert_eq!(mesh.positions[1], glam::vec3(1.0, 0.0, 0.0));
+        assert_eq!(mesh.texcoords[2], glam::vec2(0.0, 1.0));
+        assert_eq!(mesh.normals, vec![glam::Vec3::Z; 3]);
+        assert_eq!(mesh.indices, [0, 1, 2]);
+        assert!(mesh.colors.is_empty());
+    }
+
+    #[test]
+    fn parses_little_endian_binary() {
+        let bytes = binary_quad("binary_little_endian", f32::to_le_bytes, i32::to_le_bytes);
+        assert_quad(&parse(&bytes).unwrap());
+    }
+
+    #[test]
+    fn parses_big_endian_binary() {
+        let bytes = binary_quad("binary_big_endian", f32::to_be_bytes, i32::to_be_bytes);
+        assert_quad(&parse(&bytes).unwrap());
+    }
+
+    #[test]
+    fn normalises_uchar_colours() {
+        let src = "ply\nformat ascii 1.0\nelement vertex 3\n\
+                   property float x\nproperty float y\nproperty float z\n\
+                   property uchar red\nproperty uchar green\nproperty uchar blue\n\
+                   element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+                   0 0 0 255 0 0\n1 0 0 0 255 0\n0 1 0 0 0 255\n3 0 1 2\n";
+        let mesh = parse(src.as_bytes()).unwrap();
+        assert_eq!(mesh.colors[0], glam::vec4(1.0, 0.0, 0.0, 1.0));
+        assert_eq!(mesh.colors[1], glam::vec4(0.0, 1.0, 0.0, 1.0));
+        assert_eq!(mesh.colors[2], glam::vec4(0.0, 0.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn keeps_quad_edges() {
+        let bytes = binary_quad("binary_little_endian", f32::to_le_bytes, i32::to_le_bytes);
+        let mesh = parse(&bytes).unwrap();
+        // Two triangles, each missing only the diagonal.
+        assert_eq!(mesh.polygon_edges.len(), 2);
+        assert!(
+            mesh.polygon_edges
+                .iter()
+                .all(|edges| edges.count_ones() == 2)
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_indices() {
+        let src = "ply\nformat ascii 1.0\nelement vertex 3\n\
+                   property float x\nproperty float y\nproperty float z\n\
+                   element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+                   0 0 0\n1 0 0\n0 1 0\n3 0 1 3\n";
+        assert_eq!(parse_error(src.as_bytes()), "face refers to vertex 3 of 3");
+    }
+
+    #[test]
+    fn rejects_a_file_that_ends_early() {
+        let src = "ply\nformat ascii 1.0\nelement vertex 3\n\
+                   property float x\nproperty float y\nproperty float z\nend_header\n\
+                   0 0 0\n1 0 0\n";
+        assert_eq!(parse_error(src.as_bytes()), "PLY file ends early");
+
+        let mut bytes = binary_quad("binary_little_endian", f32::to_le_bytes, i32::to_le_bytes);
+        bytes.truncate(bytes.len() - 2);
+        assert_eq!(parse_error(&bytes), "PLY file ends early");
+
+        assert_eq!(
+            parse_error(b"ply\nformat ascii 1.0\nelement vertex 3\n"),
+            "PLY header has no `end_header`"
+        );
+    }
+
+    #[test]
+    fn reads_the_header() {
+        let src = b"ply\nformat binary_big_endian 1.0\nobj_info scanner\nelement vertex 2\n\
+                    property double x\nproperty list ushort uint vertex_indices\n\
+                    element edge 0\nend_header\nrest";
+        let (format, elements, body) = parse_header(src).unwrap();
+        assert_eq!(format, Format::BinaryBigEndian);
+        assert_eq!(elements.len(), 2);
+        assert_eq!(elements[0].name, "vertex");
+        assert_eq!(elements[0].count, 2);
+        assert_eq!(elements[0].properties[0].scalar, Scalar::F64);
+        assert_eq!(elements[0].properties[1].list, Some(Scalar::U16));
+        assert_eq!(elements[0].properties[1].scalar, Scalar::U32);
+        assert_eq!(elements[1].name, "edge");
+        assert_eq!(body, b"rest");
+
+        assert!(parse_header(b"ply\nend_header\n").is_err());
+        assert!(parse_header(b"ply\nformat ascii 1.0\nproperty float x\nend_header\n").is_err());
+    }
+}
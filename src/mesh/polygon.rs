@@ -0,0 +1,191 @@
+//! Splitting polygons into triangles by ear clipping.
+
+use glam::{Vec2, Vec3};
+
+/// The triangles of the polygon through `corners`, with the bits of each triangle's
+/// edges that are edges of the polygon, bit `i` for the edge facing corner `i`.
+///
+/// The polygon is flattened onto the plane it lies closest to, so it may be concave, or
+/// have holes joined to its outline by a pair of coincident edges. Triangles keep the
+/// polygon's winding.
+pub fn triangulate(positions: &[Vec3], corners: &[u32]) -> Vec<([u32; 3], u32)> {
+    let n = corners.len();
+    if n < 3 {
+        return Vec::new();
+    }
+    if n == 3 {
+        return vec![([corners[0], corners[1], corners[2]], 0b111)];
+    }
+
+    // Newell's method: the sum of the edges' cross products is twice the vector area,
+    // which points along the normal for any winding and tolerates warped polygons.
+    let corner = |i: usize| positions[corners[i] as usize];
+    let normal = (0..n)
+        .map(|i| corner(i).cross(corner((i + 1) % n)))
+        .sum::<Vec3>()
+        .normalize_or_zero();
+    if normal == Vec3::ZERO {
+        return fan(corners);
+    }
+    // With (u, v, normal) right handed the polygon winds counter-clockwise in 2D.
+    let u = normal.any_orthonormal_vector();
+    let v = normal.cross(u);
+    let points: Vec<Vec2> = (0..n)
+        .map(|i| Vec2::new(corner(i).dot(u), corner(i).dot(v)))
+        .collect();
+
+    // Indices into `corners` of the part that hasn't been clipped yet.
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut triangles = Vec::with_capacity(n - 2);
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let around = |i: usize| {
+            [
+                remaining[(i + len - 1) % len],
+                remaining[i],
+                remaining[(i + 1) % len],
+            ]
+        };
+        let convex =
+            |[a, b, c]: [usize; 3]| (points[b] - points[a]).perp_dot(points[c] - points[b]) > 0.0;
+        let is_ear = |triangle: [usize; 3]| {
+            convex(triangle)
+                && remaining.iter().all(|&other| {
+                    // Corners shared through a hole's bridge don't block the ear.
+                    triangle
+                        .iter()
+                        .any(|&corner| points[corner] == points[other])
+                        || !contains(triangle.map(|corner| points[corner]), points[other])
+                })
+        };
+
+        // A self-intersecting or degenerate outline may have no ears left; clipping a
+        // convex corner, or failing that any corner, still covers it.
+        let i = (0..len)
+            .find(|&i| is_ear(around(i)))
+            .or_else(|| (0..len).find(|&i| convex(around(i))))
+            .unwrap_or(0);
+        let triangle = around(i);
+        triangles.push((triangle.map(|i| corners[i]), edges(triangle, n)));
+        remaining.remove(i);
+    }
+    let triangle = [remaining[0], remaining[1], remaining[2]];
+    triangles.push((triangle.map(|i| corners[i]), edges(triangle, n)));
+    triangles
+}
+
+/// A fan from the first corner, for polygons too degenerate to have a plane.
+fn fan(corners: &[u32]) -> Vec<([u32; 3], u32)> {
+    let n = corners.len();
+    (1..n - 1)
+        .map(|i| {
+            let triangle = [0, i, i + 1];
+            (triangle.map(|i| corners[i]), edges(triangle, n))
+        })
+        .collect()
+}
+
+/// The edge bits of a triangle cut from a polygon of `n` corners: an edge is the
+/// polygon's when its corners are neighbours on the outline.
+fn edges([a, b, c]: [usize; 3], n: usize) -> u32 {
+    let outline = |from: usize, to: usize| (from + 1) % n == to;
+    (outline(b, c) as u32) | (outline(c, a) as u32) << 1 | (outline(a, b) as u32) << 2
+}
+
+/// Whether `point` is inside the counter-clockwise triangle or on its edges.
+fn contains([a, b, c]: [Vec2; 3], point: Vec2) -> bool {
+    (b - a).perp_dot(point - a) >= 0.0
+        && (c - b).perp_dot(point - b) >= 0.0
+        && (a - c).perp_dot(point - c) >= 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(xy: &[(f32, f32)]) -> Vec<Vec3> {
+        xy.iter().map(|&(x, y)| Vec3::new(x, y, 0.0)).collect()
+    }
+
+    /// Signed area of the triangle, positive when counter-clockwise seen from +z.
+    fn area(positions: &[Vec3], triangle: [u32; 3]) -> f32 {
+        let [a, b, c] = triangle.map(|i| positions[i as usize]);
+        (b - a).cross(c - a).z / 2.0
+    }
+
+    #[test]
+    fn clips_a_concave_polygon() {
+        let positions = points(&[(0., 0.), (2., 0.), (2., 1.), (1., 1.), (1., 2.), (0., 2.)]);
+        let triangles = triangulate(&positions, &[0, 1, 2, 3, 4, 5]);
+        assert_eq!(triangles.len(), 4);
+        assert!(
+            triangles
+                .iter()
+                .all(|&(triangle, _)| area(&positions, triangle) > 0.0)
+        );
+        let total: f32 = triangles
+            .iter()
+            .map(|&(triangle, _)| area(&positions, triangle))
+            .sum();
+        assert!((total - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn leaves_a_bridged_hole_empty() {
+        let positions = points(&[
+            (0., 0.),
+            (4., 0.),
+            (4., 4.),
+            (0., 4.),
+            (1., 1.),
+            (1., 3.),
+            (3., 3.),
+            (3., 1.),
+        ]);
+        // The outline, across to the hole, clockwise around it and back.
+        let corners = [0, 1, 2, 3, 0, 4, 5, 6, 7, 4];
+        let triangles = triangulate(&positions, &corners);
+        assert_eq!(triangles.len(), corners.len() - 2);
+
+        let hole = |p: Vec3| p.x > 1.0 && p.x < 3.0 && p.y > 1.0 && p.y < 3.0;
+        for &(triangle, _) in &triangles {
+            assert!(area(&positions, triangle) >= 0.0);
+            let [a, b, c] = triangle.map(|i| positions[i as usize]);
+            assert!(!hole((a + b + c) / 3.0), "{triangle:?} covers the hole");
+        }
+        let total: f32 = triangles
+            .iter()
+            .map(|&(triangle, _)| area(&positions, triangle))
+            .sum();
+        assert!((total - 12.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn survives_degenerate_polygons() {
+        // A corner in the middle of an edge.
+        let positions = points(&[(0., 0.), (1., 0.), (2., 0.), (2., 2.), (0., 2.)]);
+        assert_eq!(triangulate(&positions, &[0, 1, 2, 3, 4]).len(), 3);
+        // Every corner on one line, so there's no plane.
+        let positions = points(&[(0., 0.), (1., 0.), (2., 0.), (3., 0.)]);
+        assert_eq!(triangulate(&positions, &[0, 1, 2, 3]).len(), 2);
+        // Repeated corners.
+        let positions = points(&[(0., 0.), (1., 0.), (1., 1.), (0., 1.)]);
+        assert_eq!(triangulate(&positions, &[0, 1, 1, 2, 3, 3]).len(), 4);
+        assert!(triangulate(&positions, &[0, 1]).is_empty());
+    }
+
+    #[test]
+    fn hides_a_quad_diagonal() {
+        let positions = points(&[(0., 0.), (1., 0.), (1., 1.), (0., 1.)]);
+        let triangles = triangulate(&positions, &[0, 1, 2, 3]);
+        assert_eq!(triangles.len(), 2);
+        for (triangle, edges) in triangles {
+            // Bit `i` is the edge facing corner `i`, between the other two.
+            for i in 0..3 {
+                let (a, b) = (triangle[(i + 1) % 3], triangle[(i + 2) % 3]);
+                let diagonal = a.abs_diff(b) == 2;
+                assert_eq!(edges & 1 << i == 0, diagonal, "{triangle:?} {edges:#b}");
+            }
+        }
+    }
+}
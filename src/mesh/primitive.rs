@@ -0,0 +1,363 @@
+//! Meshes generated in code, so a shader can be tried without a model file.
+//!
+//! Everything is y up and about two units across, centred on the origin except the
+//! plane, which lies in it.
+
+use std::f32::consts::{PI, TAU};
+
+use glam::{Vec2, Vec3, vec3};
+
+use super::{Mesh, teapot};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Primitive {
+    UvSphere {
+        segments: u32,
+        rings: u32,
+    },
+    Icosphere {
+        subdivisions: u32,
+    },
+    Cube {
+        subdivisions: u32,
+    },
+    Plane {
+        subdivisions: u32,
+        size: f32,
+    },
+    Cylinder {
+        segments: u32,
+        height: f32,
+    },
+    /// A ring of radius 1 around the y axis, with a tube of radius `thickness`.
+    Torus {
+        segments: u32,
+        sides: u32,
+        thickness: f32,
+    },
+    /// `tessellation` is the number of quads along each side of the 32 patches.
+    Teapot {
+        tessellation: u32,
+    },
+}
+
+impl Primitive {
+    /// Every primitive with its default parameters.
+    pub const ALL: [Primitive; 7] = [
+        Primitive::UvSphere {
+            segments: 32,
+            rings: 16,
+        },
+        Primitive::Icosphere { subdivisions: 3 },
+        Primitive::Cube { subdivisions: 1 },
+        Primitive::Plane {
+            subdivisions: 1,
+            size: 2.0,
+        },
+        Primitive::Cylinder {
+            segments: 32,
+            height: 2.0,
+        },
+        Primitive::Torus {
+            segments: 48,
+            sides: 24,
+            thickness: 0.3,
+        },
+        Primitive::Teapot { tessellation: 8 },
+    ];
+
+    /// Edits the parameters. Returns true when the mesh has to be generated again.
+    pub fn build_widget(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        let mut count = |ui: &mut egui::Ui, label: &str, value: &mut u32, range| {
+            ui.label(format!("{label}: {value}"));
+            changed |= ui.add(egui::Slider::new(value, range)).changed();
+        };
+        match self {
+            Primitive::UvSphere { segments, rings } => {
+                count(ui, "segments", segments, 3..=128);
+                count(ui, "rings", rings, 2..=64);
+            }
+            Primitive::Icosphere { subdivisions } => count(ui, "subdivisions", subdivisions, 0..=5),
+            Primitive::Cube { subdivisions } => count(ui, "subdivisions", subdivisions, 1..=64),
+            Primitive::Plane { subdivisions, size } => {
+                count(ui, "subdivisions", subdivisions, 1..=256);
+                ui.label(format!("size: {size}"));
+                changed |= ui.add(egui::Slider::new(size, 0.1..=100.0)).changed();
+            }
+            Primitive::Cylinder { segments, height } => {
+                count(ui, "segments", segments, 3..=128);
+                ui.label(format!("height: {height}"));
+                changed |= ui.add(egui::Slider::new(height, 0.1..=10.0)).changed();
+            }
+            Primitive::Torus {
+                segments,
+                sides,
+                thickness,
+            } => {
+                count(ui, "segments", segments, 3..=128);
+                count(ui, "sides", sides, 3..=64);
+                ui.label(format!("thickness: {thickness}"));
+                changed |= ui.add(egui::Slider::new(thickness, 0.01..=1.0)).changed();
+            }
+            Primitive::Teapot { tessellation } => count(ui, "tessellation", tessellation, 1..=32),
+        }
+        changed
+    }
+
+    /// Whether `other` is the same primitive, maybe with other parameters.
+    pub fn same_kind(&self, other: &Primitive) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    pub fn mesh(&self) -> Mesh {
+        let mut mesh = Mesh::default();
+        match *self {
+            Primitive::UvSphere { segments, rings } => {
+                grid(&mut mesh, segments, rings, |u, v| {
+                    let (phi, theta) = (u * TAU, v * PI);
+                    let normal = vec3(
+                        theta.sin() * phi.cos(),
+                        -theta.cos(),
+                        -theta.sin() * phi.sin(),
+                    );
+                    (normal, normal)
+                });
+            }
+            Primitive::Icosphere { subdivisions } => icosphere(&mut mesh, subdivisions),
+            Primitive::Cube { subdivisions } => {
+                for (normal, right, up) in [
+                    (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+                    (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+                    (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+                    (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+                    (Vec3::Z, Vec3::X, Vec3::Y),
+                    (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+                ] {
+                    grid(&mut mesh, subdivisions, subdivisions, |u, v| {
+                        let position = normal + right * (u * 2.0 - 1.0) + up * (v * 2.0 - 1.0);
+                        (position, normal)
+                    });
+                }
+            }
+            Primitive::Plane { subdivisions, size } => {
+                grid(&mut mesh, subdivisions, subdivisions, |u, v| {
+                    (vec3(u - 0.5, 0.0, 0.5 - v) * size, Vec3::Y)
+                });
+            }
+            Primitive::Cylinder { segments, height } => {
+                let half = height * 0.5;
+                let around = |u: f32| vec3((u * TAU).cos(), 0.0, -(u * TAU).sin());
+                grid(&mut mesh, segments, 1, |u, v| {
+                    (around(u) + Vec3::Y * (v * height - half), around(u))
+                });
+                // The caps run from the rim at v = 0 to the centre at v = 1 on top, and
+                // the other way round underneath.
+                grid(&mut mesh, segments, 1, |u, v| {
+                    (around(u) * (1.0 - v) + Vec3::Y * half, Vec3::Y)
+                });
+                grid(&mut mesh, segments, 1, |u, v| {
+                    (around(u) * v - Vec3::Y * half, Vec3::NEG_Y)
+                });
+            }
+            Primitive::Torus {
+                segments,
+                sides,
+                thickness,
+            } => {
+                grid(&mut mesh, segments, sides, |u, v| {
+                    let (phi, theta) = (u * TAU, v * TAU);
+                    let center = vec3(phi.cos(), 0.0, -phi.sin());
+                    let normal = center * theta.cos() + Vec3::Y * theta.sin();
+                    (center + normal * thickness, normal)
+                });
+            }
+            Primitive::Teapot { tessellation } => {
+                for patch in teapot::PATCHES {
+                    let points = patch.map(|i| {
+                        // z up to y up, scaled so the body has a radius of 1, then
+                        // centred on its height.
+                        let [x, y, z] = teapot::POINTS[i as usize];
+                        vec3(x, z - 1.575, -y) * 0.5
+                    });
+                    grid(&mut mesh, tessellation, tessellation, |u, v| {
+                        bezier_patch(&points, u, v)
+                    });
+                }
+            }
+        }
+        mesh.compute_tangents();
+        mesh
+    }
+}
+
+impl std::fmt::Display for Primitive {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Primitive::UvSphere { .. } => "UV sphere",
+            Primitive::Icosphere { .. } => "Icosphere",
+            Primitive::Cube { .. } => "Cube",
+            Primitive::Plane { .. } => "Plane",
+            Primitive::Cylinder { .. } => "Cylinder",
+            Primitive::Torus { .. } => "Torus",
+            Primitive::Teapot { .. } => "Teapot",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Adds a `columns` by `rows` grid of quads over `surface(u, v)`, which returns the
+/// position and normal at u and v between 0 and 1. Those also become the texture
+/// coordinates. The grid faces the side ∂p/∂u × ∂p/∂v points to, and its quads keep
+/// their diagonals out of the wireframe.
+fn grid(mesh: &mut Mesh, columns: u32, rows: u32, surface: impl Fn(f32, f32) -> (Vec3, Vec3)) {
+    let first = mesh.positions.len() as u32;
+    for row in 0..=rows {
+        for column in 0..=columns {
+            let (u, v) = (column as f32 / columns as f32, row as f32 / rows as f32);
+            let (position, normal) = surface(u, v);
+            mesh.positions.push(position);
+            mesh.normals.push(normal);
+            mesh.texcoords.push(Vec2::new(u, v));
+        }
+    }
+
+    let index = |column: u32, row: u32| first + row * (columns + 1) + column;
+    mesh.polygon_edges.resize(mesh.indices.len() / 3, 0b111);
+    for row in 0..rows {
+        for column in 0..columns {
+            let (a, b) = (index(column, row), index(column + 1, row));
+            let (c, d) = (index(column, row + 1), index(column + 1, row + 1));
+            mesh.indices.extend([a, b, c, b, d, c]);
+            // The wireframe shows quads: the diagonal from b to c faces a, then d.
+            mesh.polygon_edges.extend([0b110, 0b101]);
+        }
+    }
+}
+
+/// Subdivided icosahedron pushed out onto the unit sphere, mapped like the UV sphere.
+fn icosphere(mesh: &mut Mesh, subdivisions: u32) {
+    let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+    let mut positions: Vec<Vec3> = [
+        [-1.0, t, 0.0],
+        [1.0, t, 0.0],
+        [-1.0, -t, 0.0],
+        [1.0, -t, 0.0],
+        [0.0, -1.0, t],
+        [0.0, 1.0, t],
+        [0.0, -1.0, -t],
+        [0.0, 1.0, -t],
+        [t, 0.0, -1.0],
+        [t, 0.0, 1.0],
+        [-t, 0.0, -1.0],
+        [-t, 0.0, 1.0],
+    ]
+    .into_iter()
+    .map(|p| Vec3::from(p).normalize())
+    .collect();
+    let mut triangles: Vec<[u32; 3]> = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Edges are shared by two triangles, which have to share the midpoint too.
+        let mut midpoints = std::collections::HashMap::new();
+        let mut midpoint = |a: u32, b: u32| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let p = (positions[a as usize] + positions[b as usize]).normalize();
+                positions.push(p);
+                positions.len() as u32 - 1
+            })
+        };
+        triangles = triangles
+            .into_iter()
+            .flat_map(|[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let texcoord = |p: Vec3| {
+        let u = (-p.z).atan2(p.x) / TAU;
+        Vec2::new(u.rem_euclid(1.0), (-p.y).clamp(-1.0, 1.0).acos() / PI)
+    };
+    mesh.texcoords = positions.iter().map(|p| texcoord(*p)).collect();
+    mesh.normals = positions.clone();
+    mesh.positions = positions;
+    for triangle in triangles {
+        // Triangles across the seam get their own copies of the vertices on the u = 0
+        // side, moved to u > 1 so the texture doesn't run backwards over them.
+        let us = triangle.map(|i| mesh.texcoords[i as usize].x);
+        let wraps = us.iter().copied().fold(f32::MIN, f32::max)
+            - us.iter().copied().fold(f32::MAX, f32::min)
+            > 0.5;
+        for i in triangle {
+            let texcoord = mesh.texcoords[i as usize];
+            if wraps && texcoord.x < 0.5 {
+                mesh.positions.push(mesh.positions[i as usize]);
+                mesh.normals.push(mesh.normals[i as usize]);
+                mesh.texcoords.push(texcoord + Vec2::X);
+                mesh.indices.push(mesh.positions.len() as u32 - 1);
+            } else {
+                mesh.indices.push(i);
+            }
+        }
+    }
+}
+
+/// Position and normal of a bicubic Bézier patch, at `u` along its rows and `v`
+/// across them.
+fn bezier_patch(points: &[Vec3; 16], u: f32, v: f32) -> (Vec3, Vec3) {
+    let basis = |t: f32| {
+        let s = 1.0 - t;
+        [s * s * s, 3.0 * t * s * s, 3.0 * t * t * s, t * t * t]
+    };
+    let derivative = |t: f32| {
+        let s = 1.0 - t;
+        [
+            -3.0 * s * s,
+            3.0 * s * (s - 2.0 * t),
+            3.0 * t * (2.0 * s - t),
+            3.0 * t * t,
+        ]
+    };
+    let evaluate = |row: [f32; 4], column: [f32; 4]| {
+        (0..16).fold(Vec3::ZERO, |sum, i| {
+            sum + points[i] * row[i / 4] * column[i % 4]
+        })
+    };
+
+    let position = evaluate(basis(v), basis(u));
+    // The lid and the bottom close up into a point, where the derivative along the
+    // rows vanishes; the normal just next to it is as good.
+    let normal_at = |u: f32, v: f32| {
+        let along = evaluate(basis(v), derivative(u));
+        let across = evaluate(derivative(v), basis(u));
+        let normal = along.cross(across);
+        (normal.length_squared() > 1e-10).then(|| normal.normalize())
+    };
+    let normal = normal_at(u, v)
+        .or_else(|| normal_at(u, v.clamp(1e-3, 1.0 - 1e-3)))
+        .unwrap_or(Vec3::Y);
+    (position, normal)
+}
@@ -0,0 +1,31 @@
+//! STL files, ASCII or binary, through stl_io.
+
+use std::{fs::File, io::BufReader, path::Path};
+
+use super::{Mesh, Model};
+
+/// The triangles of the file, with normals computed from their corners. The facet
+/// normals STL stores are often zero or point the wrong way, so they're ignored.
+pub fn load(path: &Path) -> Result<Model, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open STL file: {e}"))?;
+    let stl = stl_io::read_stl(&mut BufReader::new(file))
+        .map_err(|e| format!("Failed to load STL file: {e}"))?;
+
+    let mut mesh = Mesh {
+        texcoords: vec![glam::Vec2::ZERO; stl.vertices.len()],
+        positions: stl
+            .vertices
+            .iter()
+            .map(|vertex| glam::Vec3::from_array(vertex.0))
+            .collect(),
+        indices: stl
+            .faces
+            .iter()
+            .flat_map(|face| face.vertices.map(|i| i as u32))
+            .collect(),
+        ..Default::default()
+    };
+    mesh.compute_flat_normals();
+    mesh.compute_tangents();
+    Ok(Model::from(mesh))
+}
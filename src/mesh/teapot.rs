@@ -0,0 +1,354 @@
+//! Newell's teapot as 32 bicubic Bézier patches, with Crow's bottom added.
+//!
+//! Coordinates are the original ones, with z up.
+
+/// Control points of [`PATCHES`].
+pub const POINTS: [[f32; 3]; 302] = [
+    [1.4, 0.0, 2.4],
+    [1.4, -0.784, 2.4],
+    [0.784, -1.4, 2.4],
+    [0.0, -1.4, 2.4],
+    [1.3375, 0.0, 2.53125],
+    [1.3375, -0.749, 2.53125],
+    [0.749, -1.3375, 2.53125],
+    [0.0, -1.3375, 2.53125],
+    [1.4375, 0.0, 2.53125],
+    [1.4375, -0.805, 2.53125],
+    [0.805, -1.4375, 2.53125],
+    [0.0, -1.4375, 2.53125],
+    [1.5, 0.0, 2.4],
+    [1.5, -0.84, 2.4],
+    [0.84, -1.5, 2.4],
+    [0.0, -1.5, 2.4],
+    [-0.784, -1.4, 2.4],
+    [-1.4, -0.784, 2.4],
+    [-1.4, 0.0, 2.4],
+    [-0.749, -1.3375, 2.53125],
+    [-1.3375, -0.749, 2.53125],
+    [-1.3375, 0.0, 2.53125],
+    [-0.805, -1.4375, 2.53125],
+    [-1.4375, -0.805, 2.53125],
+    [-1.4375, 0.0, 2.53125],
+    [-0.84, -1.5, 2.4],
+    [-1.5, -0.84, 2.4],
+    [-1.5, 0.0, 2.4],
+    [-1.4, 0.784, 2.4],
+    [-0.784, 1.4, 2.4],
+    [0.0, 1.4, 2.4],
+    [-1.3375, 0.749, 2.53125],
+    [-0.749, 1.3375, 2.53125],
+    [0.0, 1.3375, 2.53125],
+    [-1.4375, 0.805, 2.53125],
+    [-0.805, 1.4375, 2.53125],
+    [0.0, 1.4375, 2.53125],
+    [-1.5, 0.84, 2.4],
+    [-0.84, 1.5, 2.4],
+    [0.0, 1.5, 2.4],
+    [0.784, 1.4, 2.4],
+    [1.4, 0.784, 2.4],
+    [0.749, 1.3375, 2.53125],
+    [1.3375, 0.749, 2.53125],
+    [0.805, 1.4375, 2.53125],
+    [1.4375, 0.805, 2.53125],
+    [0.84, 1.5, 2.4],
+    [1.5, 0.84, 2.4],
+    [1.75, 0.0, 1.875],
+    [1.75, -0.98, 1.875],
+    [0.98, -1.75, 1.875],
+    [0.0, -1.75, 1.875],
+    [2.0, 0.0, 1.35],
+    [2.0, -1.12, 1.35],
+    [1.12, -2.0, 1.35],
+    [0.0, -2.0, 1.35],
+    [2.0, 0.0, 0.9],
+    [2.0, -1.12, 0.9],
+    [1.12, -2.0, 0.9],
+    [0.0, -2.0, 0.9],
+    [-0.98, -1.75, 1.875],
+    [-1.75, -0.98, 1.875],
+    [-1.75, 0.0, 1.875],
+    [-1.12, -2.0, 1.35],
+    [-2.0, -1.12, 1.35],
+    [-2.0, 0.0, 1.35],
+    [-1.12, -2.0, 0.9],
+    [-2.0, -1.12, 0.9],
+    [-2.0, 0.0, 0.9],
+    [-1.75, 0.98, 1.875],
+    [-0.98, 1.75, 1.875],
+    [0.0, 1.75, 1.875],
+    [-2.0, 1.12, 1.35],
+    [-1.12, 2.0, 1.35],
+    [0.0, 2.0, 1.35],
+    [-2.0, 1.12, 0.9],
+    [-1.12, 2.0, 0.9],
+    [0.0, 2.0, 0.9],
+    [0.98, 1.75, 1.875],
+    [1.75, 0.98, 1.875],
+    [1.12, 2.0, 1.35],
+    [2.0, 1.12, 1.35],
+    [1.12, 2.0, 0.9],
+    [2.0, 1.12, 0.9],
+    [2.0, 0.0, 0.45],
+    [2.0, -1.12, 0.45],
+    [1.12, -2.0, 0.45],
+    [0.0, -2.0, 0.45],
+    [1.5, 0.0, 0.225],
+    [1.5, -0.84, 0.225],
+    [0.84, -1.5, 0.225],
+    [0.0, -1.5, 0.225],
+    [1.5, 0.0, 0.15],
+    [1.5, -0.84, 0.15],
+    [0.84, -1.5, 0.15],
+    [0.0, -1.5, 0.15],
+    [-1.12, -2.0, 0.45],
+    [-2.0, -1.12, 0.45],
+    [-2.0, 0.0, 0.45],
+    [-0.84, -1.5, 0.225],
+    [-1.5, -0.84, 0.225],
+    [-1.5, 0.0, 0.225],
+    [-0.84, -1.5, 0.15],
+    [-1.5, -0.84, 0.15],
+    [-1.5, 0.0, 0.15],
+    [-2.0, 1.12, 0.45],
+    [-1.12, 2.0, 0.45],
+    [0.0, 2.0, 0.45],
+    [-1.5, 0.84, 0.225],
+    [-0.84, 1.5, 0.225],
+    [0.0, 1.5, 0.225],
+    [-1.5, 0.84, 0.15],
+    [-0.84, 1.5, 0.15],
+    [0.0, 1.5, 0.15],
+    [1.12, 2.0, 0.45],
+    [2.0, 1.12, 0.45],
+    [0.84, 1.5, 0.225],
+    [1.5, 0.84, 0.225],
+    [0.84, 1.5, 0.15],
+    [1.5, 0.84, 0.15],
+    [-1.6, 0.0, 2.025],
+    [-1.6, -0.3, 2.025],
+    [-1.5, -0.3, 2.25],
+    [-1.5, 0.0, 2.25],
+    [-2.3, 0.0, 2.025],
+    [-2.3, -0.3, 2.025],
+    [-2.5, -0.3, 2.25],
+    [-2.5, 0.0, 2.25],
+    [-2.7, 0.0, 2.025],
+    [-2.7, -0.3, 2.025],
+    [-3.0, -0.3, 2.25],
+    [-3.0, 0.0, 2.25],
+    [-2.7, 0.0, 1.8],
+    [-2.7, -0.3, 1.8],
+    [-3.0, -0.3, 1.8],
+    [-3.0, 0.0, 1.8],
+    [-1.5, 0.3, 2.25],
+    [-1.6, 0.3, 2.025],
+    [-2.5, 0.3, 2.25],
+    [-2.3, 0.3, 2.025],
+    [-3.0, 0.3, 2.25],
+    [-2.7, 0.3, 2.025],
+    [-3.0, 0.3, 1.8],
+    [-2.7, 0.3, 1.8],
+    [-2.7, 0.0, 1.575],
+    [-2.7, -0.3, 1.575],
+    [-3.0, -0.3, 1.35],
+    [-3.0, 0.0, 1.35],
+    [-2.5, 0.0, 1.125],
+    [-2.5, -0.3, 1.125],
+    [-2.65, -0.3, 0.9375],
+    [-2.65, 0.0, 0.9375],
+    [-2.0, -0.3, 0.9],
+    [-1.9, -0.3, 0.6],
+    [-1.9, 0.0, 0.6],
+    [-3.0, 0.3, 1.35],
+    [-2.7, 0.3, 1.575],
+    [-2.65, 0.3, 0.9375],
+    [-2.5, 0.3, 1.125],
+    [-1.9, 0.3, 0.6],
+    [-2.0, 0.3, 0.9],
+    [1.7, 0.0, 1.425],
+    [1.7, -0.66, 1.425],
+    [1.7, -0.66, 0.6],
+    [1.7, 0.0, 0.6],
+    [2.6, 0.0, 1.425],
+    [2.6, -0.66, 1.425],
+    [3.1, -0.66, 0.825],
+    [3.1, 0.0, 0.825],
+    [2.3, 0.0, 2.1],
+    [2.3, -0.25, 2.1],
+    [2.4, -0.25, 2.025],
+    [2.4, 0.0, 2.025],
+    [2.7, 0.0, 2.4],
+    [2.7, -0.25, 2.4],
+    [3.3, -0.25, 2.4],
+    [3.3, 0.0, 2.4],
+    [1.7, 0.66, 0.6],
+    [1.7, 0.66, 1.425],
+    [3.1, 0.66, 0.825],
+    [2.6, 0.66, 1.425],
+    [2.4, 0.25, 2.025],
+    [2.3, 0.25, 2.1],
+    [3.3, 0.25, 2.4],
+    [2.7, 0.25, 2.4],
+    [2.8, 0.0, 2.475],
+    [2.8, -0.25, 2.475],
+    [3.525, -0.25, 2.49375],
+    [3.525, 0.0, 2.49375],
+    [2.9, 0.0, 2.475],
+    [2.9, -0.15, 2.475],
+    [3.45, -0.15, 2.5125],
+    [3.45, 0.0, 2.5125],
+    [2.8, 0.0, 2.4],
+    [2.8, -0.15, 2.4],
+    [3.2, -0.15, 2.4],
+    [3.2, 0.0, 2.4],
+    [3.525, 0.25, 2.49375],
+    [2.8, 0.25, 2.475],
+    [3.45, 0.15, 2.5125],
+    [2.9, 0.15, 2.475],
+    [3.2, 0.15, 2.4],
+    [2.8, 0.15, 2.4],
+    [0.0, 0.0, 3.15],
+    [0.8, 0.0, 3.15],
+    [0.8, -0.45, 3.15],
+    [0.45, -0.8, 3.15],
+    [0.0, -0.8, 3.15],
+    [0.0, 0.0, 2.85],
+    [0.2, 0.0, 2.7],
+    [0.2, -0.112, 2.7],
+    [0.112, -0.2, 2.7],
+    [0.0, -0.2, 2.7],
+    [-0.45, -0.8, 3.15],
+    [-0.8, -0.45, 3.15],
+    [-0.8, 0.0, 3.15],
+    [-0.112, -0.2, 2.7],
+    [-0.2, -0.112, 2.7],
+    [-0.2, 0.0, 2.7],
+    [-0.8, 0.45, 3.15],
+    [-0.45, 0.8, 3.15],
+    [0.0, 0.8, 3.15],
+    [-0.2, 0.112, 2.7],
+    [-0.112, 0.2, 2.7],
+    [0.0, 0.2, 2.7],
+    [0.45, 0.8, 3.15],
+    [0.8, 0.45, 3.15],
+    [0.112, 0.2, 2.7],
+    [0.2, 0.112, 2.7],
+    [0.4, 0.0, 2.55],
+    [0.4, -0.224, 2.55],
+    [0.224, -0.4, 2.55],
+    [0.0, -0.4, 2.55],
+    [1.3, 0.0, 2.55],
+    [1.3, -0.728, 2.55],
+    [0.728, -1.3, 2.55],
+    [0.0, -1.3, 2.55],
+    [1.3, 0.0, 2.4],
+    [1.3, -0.728, 2.4],
+    [0.728, -1.3, 2.4],
+    [0.0, -1.3, 2.4],
+    [-0.224, -0.4, 2.55],
+    [-0.4, -0.224, 2.55],
+    [-0.4, 0.0, 2.55],
+    [-0.728, -1.3, 2.55],
+    [-1.3, -0.728, 2.55],
+    [-1.3, 0.0, 2.55],
+    [-0.728, -1.3, 2.4],
+    [-1.3, -0.728, 2.4],
+    [-1.3, 0.0, 2.4],
+    [-0.4, 0.224, 2.55],
+    [-0.224, 0.4, 2.55],
+    [0.0, 0.4, 2.55],
+    [-1.3, 0.728, 2.55],
+    [-0.728, 1.3, 2.55],
+    [0.0, 1.3, 2.55],
+    [-1.3, 0.728, 2.4],
+    [-0.728, 1.3, 2.4],
+    [0.0, 1.3, 2.4],
+    [0.224, 0.4, 2.55],
+    [0.4, 0.224, 2.55],
+    [0.728, 1.3, 2.55],
+    [1.3, 0.728, 2.55],
+    [0.728, 1.3, 2.4],
+    [1.3, 0.728, 2.4],
+    [0.0, 0.0, 0.0],
+    [1.5, 0.0, 0.15],
+    [1.5, 0.84, 0.15],
+    [0.84, 1.5, 0.15],
+    [0.0, 1.5, 0.15],
+    [1.5, 0.0, 0.075],
+    [1.5, 0.84, 0.075],
+    [0.84, 1.5, 0.075],
+    [0.0, 1.5, 0.075],
+    [1.425, 0.0, 0.0],
+    [1.425, 0.798, 0.0],
+    [0.798, 1.425, 0.0],
+    [0.0, 1.425, 0.0],
+    [-0.84, 1.5, 0.15],
+    [-1.5, 0.84, 0.15],
+    [-1.5, 0.0, 0.15],
+    [-0.84, 1.5, 0.075],
+    [-1.5, 0.84, 0.075],
+    [-1.5, 0.0, 0.075],
+    [-0.798, 1.425, 0.0],
+    [-1.425, 0.798, 0.0],
+    [-1.425, 0.0, 0.0],
+    [-1.5, -0.84, 0.15],
+    [-0.84, -1.5, 0.15],
+    [0.0, -1.5, 0.15],
+    [-1.5, -0.84, 0.075],
+    [-0.84, -1.5, 0.075],
+    [0.0, -1.5, 0.075],
+    [-1.425, -0.798, 0.0],
+    [-0.798, -1.425, 0.0],
+    [0.0, -1.425, 0.0],
+    [0.84, -1.5, 0.15],
+    [1.5, -0.84, 0.15],
+    [0.84, -1.5, 0.075],
+    [1.5, -0.84, 0.075],
+    [0.798, -1.425, 0.0],
+    [1.425, -0.798, 0.0],
+];
+
+/// Each patch's 4×4 control points, row by row, as indices into [`POINTS`]. A patch
+/// faces the side the derivative along its rows, crossed with the derivative across
+/// them, points to.
+#[rustfmt::skip]
+pub const PATCHES: [[u16; 16]; 32] = [
+    // Rim
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [3, 16, 17, 18, 7, 19, 20, 21, 11, 22, 23, 24, 15, 25, 26, 27],
+    [18, 28, 29, 30, 21, 31, 32, 33, 24, 34, 35, 36, 27, 37, 38, 39],
+    [30, 40, 41, 0, 33, 42, 43, 4, 36, 44, 45, 8, 39, 46, 47, 12],
+    // Body
+    [12, 13, 14, 15, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59],
+    [15, 25, 26, 27, 51, 60, 61, 62, 55, 63, 64, 65, 59, 66, 67, 68],
+    [27, 37, 38, 39, 62, 69, 70, 71, 65, 72, 73, 74, 68, 75, 76, 77],
+    [39, 46, 47, 12, 71, 78, 79, 48, 74, 80, 81, 52, 77, 82, 83, 56],
+    [56, 57, 58, 59, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95],
+    [59, 66, 67, 68, 87, 96, 97, 98, 91, 99, 100, 101, 95, 102, 103, 104],
+    [68, 75, 76, 77, 98, 105, 106, 107, 101, 108, 109, 110, 104, 111, 112, 113],
+    [77, 82, 83, 56, 107, 114, 115, 84, 110, 116, 117, 88, 113, 118, 119, 92],
+    // Handle
+    [120, 121, 122, 123, 124, 125, 126, 127, 128, 129, 130, 131, 132, 133, 134, 135],
+    [123, 136, 137, 120, 127, 138, 139, 124, 131, 140, 141, 128, 135, 142, 143, 132],
+    [132, 133, 134, 135, 144, 145, 146, 147, 148, 149, 150, 151, 68, 152, 153, 154],
+    [135, 142, 143, 132, 147, 155, 156, 144, 151, 157, 158, 148, 154, 159, 160, 68],
+    // Spout
+    [161, 162, 163, 164, 165, 166, 167, 168, 169, 170, 171, 172, 173, 174, 175, 176],
+    [164, 177, 178, 161, 168, 179, 180, 165, 172, 181, 182, 169, 176, 183, 184, 173],
+    [173, 174, 175, 176, 185, 186, 187, 188, 189, 190, 191, 192, 193, 194, 195, 196],
+    [176, 183, 184, 173, 188, 197, 198, 185, 192, 199, 200, 189, 196, 201, 202, 193],
+    // Lid
+    [203, 203, 203, 203, 204, 205, 206, 207, 208, 208, 208, 208, 209, 210, 211, 212],
+    [203, 203, 203, 203, 207, 213, 214, 215, 208, 208, 208, 208, 212, 216, 217, 218],
+    [203, 203, 203, 203, 215, 219, 220, 221, 208, 208, 208, 208, 218, 222, 223, 224],
+    [203, 203, 203, 203, 221, 225, 226, 204, 208, 208, 208, 208, 224, 227, 228, 209],
+    [209, 210, 211, 212, 229, 230, 231, 232, 233, 234, 235, 236, 237, 238, 239, 240],
+    [212, 216, 217, 218, 232, 241, 242, 243, 236, 244, 245, 246, 240, 247, 248, 249],
+    [218, 222, 223, 224, 243, 250, 251, 252, 246, 253, 254, 255, 249, 256, 257, 258],
+    [224, 227, 228, 209, 252, 259, 260, 229, 255, 261, 262, 233, 258, 263, 264, 237],
+    // Bottom
+    [265, 265, 265, 265, 274, 275, 276, 277, 270, 271, 272, 273, 266, 267, 268, 269],
+    [265, 265, 265, 265, 277, 284, 285, 286, 273, 281, 282, 283, 269, 278, 279, 280],
+    [265, 265, 265, 265, 286, 293, 294, 295, 283, 290, 291, 292, 280, 287, 288, 289],
+    [265, 265, 265, 265, 295, 300, 301, 274, 292, 298, 299, 270, 289, 296, 297, 266],
+];
@@ -1,205 +1,442 @@
-use eframe::egui_wgpu::wgpu::util::DeviceExt as _;
-use eframe::egui_wgpu::wgpu;
-
-const DEFAULT_OBJECT_PATH: &str = "./objects/test1.obj";
-
 use egui_file::FileDialog;
 use std::{
     ffi::OsStr,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+    time::Instant,
+};
+
+use crate::{
+    config::{ShadingModel, ShadingModelEnum},
+    mesh::{Model, primitive::Primitive},
+    watch::FileWatcher,
 };
 
-use crate::renderer::ObjectRenderResources;
+/// Extensions of the files "Load 3D model" offers.
+const MODEL_EXTENSIONS: [&str; 5] = ["obj", "gltf", "glb", "stl", "ply"];
+
+/// Axis-aligned box and bounding sphere of a mesh, in model space.
+#[derive(Clone, Copy, Debug)]
+pub struct Bounds {
+    pub min: glam::Vec3,
+    pub max: glam::Vec3,
+    pub center: glam::Vec3,
+    pub radius: f32,
+}
+
+impl Bounds {
+    /// Bounds of flat xyz `positions`. The sphere is centred on the box, with the
+    /// radius reaching the farthest point.
+    pub fn from_positions(positions: &[f32]) -> Bounds {
+        let points = || positions.chunks_exact(3).map(glam::Vec3::from_slice);
+        let (min, max) = points().fold(
+            (glam::Vec3::INFINITY, glam::Vec3::NEG_INFINITY),
+            |(min, max), p| (min.min(p), max.max(p)),
+        );
+        if min.cmpgt(max).any() {
+            return Bounds {
+                min: glam::Vec3::ZERO,
+                max: glam::Vec3::ZERO,
+                center: glam::Vec3::ZERO,
+                radius: 0.0,
+            };
+        }
+
+        let center = (min + max) * 0.5;
+        let radius = points().map(|p| p.distance(center)).fold(0.0, f32::max);
+        Bounds {
+            min,
+            max,
+            center,
+            radius,
+        }
+    }
+
+    /// Bounds of the mesh placed by `model`, still as an axis-aligned box.
+    pub fn transformed(&self, model: glam::Mat4) -> Bounds {
+        let (min, max) = (0..8)
+            .map(|corner| {
+                let select =
+                    |bit: u32, min: f32, max: f32| if corner & bit == 0 { min } else { max };
+                model.transform_point3(glam::vec3(
+                    select(1, self.min.x, self.max.x),
+                    select(2, self.min.y, self.max.y),
+                    select(4, self.min.z, self.max.z),
+                ))
+            })
+            .fold(
+                (glam::Vec3::INFINITY, glam::Vec3::NEG_INFINITY),
+                |(min, max), p| (min.min(p), max.max(p)),
+            );
+        let (scale, _, _) = model.to_scale_rotation_translation();
+        Bounds {
+            min,
+            max,
+            center: model.transform_point3(self.center),
+            radius: self.radius * scale.abs().max_element(),
+        }
+    }
+}
+
+/// Places the object in the world: scale, then rotation, then translation.
+#[derive(Clone, Copy, Debug)]
+pub struct Transform {
+    pub translation: glam::Vec3,
+    /// XYZ Euler angles in degrees.
+    pub rotation: glam::Vec3,
+    pub scale: glam::Vec3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            translation: glam::Vec3::ZERO,
+            rotation: glam::Vec3::ZERO,
+            scale: glam::Vec3::ONE,
+        }
+    }
+}
+
+impl Transform {
+    pub fn rotation_quat(&self) -> glam::Quat {
+        let radians = self.rotation * std::f32::consts::PI / 180.0;
+        glam::Quat::from_euler(glam::EulerRot::XYZ, radians.x, radians.y, radians.z)
+    }
 
-#[derive(Default)]
+    pub fn set_rotation_quat(&mut self, rotation: glam::Quat) {
+        let (x, y, z) = rotation.to_euler(glam::EulerRot::XYZ);
+        self.rotation = glam::vec3(x, y, z) * 180.0 / std::f32::consts::PI;
+    }
+
+    pub fn matrix(&self) -> glam::Mat4 {
+        glam::Mat4::from_scale_rotation_translation(
+            self.scale,
+            self.rotation_quat(),
+            self.translation,
+        )
+    }
+
+    pub fn build_widget(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("object_transform").show(ui, |ui| {
+            for (label, value, speed) in [
+                ("position:", &mut self.translation, 0.01),
+                ("rotation:", &mut self.rotation, 0.5),
+                ("scale:", &mut self.scale, 0.01),
+            ] {
+                ui.label(label);
+                ui.add(egui::DragValue::new(&mut value.x).speed(speed).prefix("x "));
+                ui.add(egui::DragValue::new(&mut value.y).speed(speed).prefix("y "));
+                ui.add(egui::DragValue::new(&mut value.z).speed(speed).prefix("z "));
+                ui.end_row();
+            }
+        });
+        if ui.button("Reset transform").clicked() {
+            *self = Transform::default();
+        }
+    }
+}
+
+/// Where an object's mesh comes from.
+#[derive(Clone, PartialEq, Debug)]
+pub enum MeshSource {
+    File(PathBuf),
+    Primitive(Primitive),
+}
+
+/// How [`Object::build_widget`] changed the mesh.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MeshEdit {
+    /// A different mesh, worth framing.
+    Replaced,
+    /// The same mesh changed: a primitive with new parameters, or a file reloaded
+    /// after it changed on disk.
+    Tweaked,
+}
+
+/// A mesh file being read on a background thread.
+struct Loading {
+    source: MeshSource,
+    /// Whether the file is being read again after it changed.
+    reload: bool,
+    started: Instant,
+    thread: JoinHandle<Result<Model, String>>,
+}
+
+/// One object of the scene: a mesh with its own placement and shading model.
 pub struct Object {
-    pub opened_file: Option<PathBuf>,
+    /// Names the object's resources in the renderer.
+    pub id: u64,
+    pub source: MeshSource,
+    /// Bounds of the loaded mesh, once one is loaded.
+    pub bounds: Option<Bounds>,
+    pub transform: Transform,
+    pub visible: bool,
+    pub shading_model: Arc<Mutex<dyn ShadingModel + Send>>,
     open_file_dialog: Option<FileDialog>,
+    /// The file replacing the mesh. The current mesh is drawn until it's ready.
+    loading: Option<Loading>,
+    /// Why the last file couldn't be loaded.
+    load_error: Option<String>,
+    /// Whether to reload the mesh when its files change on disk.
+    pub watch: bool,
+    /// The files the current mesh was read from.
+    files: Vec<PathBuf>,
+    watcher: Option<FileWatcher>,
 }
 
 impl Object {
-    pub fn load_obj(
+    /// A Phong shaded teapot, once its mesh is loaded.
+    pub fn new(id: u64) -> Object {
+        Object {
+            id,
+            source: MeshSource::Primitive(Primitive::Teapot { tessellation: 8 }),
+            bounds: None,
+            transform: Transform::default(),
+            visible: true,
+            shading_model: ShadingModelEnum::Phong.create(),
+            open_file_dialog: None,
+            loading: None,
+            load_error: None,
+            watch: false,
+            files: Vec::new(),
+            watcher: None,
+        }
+    }
+
+    /// The file the mesh was loaded from, unless it's generated.
+    fn path(&self) -> Option<&Path> {
+        match &self.source {
+            MeshSource::File(path) => Some(path),
+            MeshSource::Primitive(_) => None,
+        }
+    }
+
+    /// The file name without its extension, or the primitive's name.
+    pub fn name(&self) -> String {
+        match &self.source {
+            MeshSource::File(path) => path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            MeshSource::Primitive(primitive) => primitive.to_string(),
+        }
+    }
+
+    /// Reads the meshes and materials from `source`, which can take a while for files.
+    fn read(source: &MeshSource) -> Result<Model, String> {
+        match source {
+            MeshSource::File(path) => Model::load(path),
+            MeshSource::Primitive(primitive) => Ok(Model::from(primitive.mesh())),
+        }
+    }
+
+    /// Reads the mesh from `source` and hands it to the renderer, blocking until done.
+    /// A file that can't be read leaves the current mesh in place.
+    pub fn load(&mut self, render_state: &egui_wgpu::RenderState) -> Bounds {
+        match Self::read(&self.source) {
+            Ok(model) => self.upload(render_state, &model, false),
+            Err(error) => {
+                self.load_error = Some(format!("Couldn't load the file: {error}"));
+                self.bounds.unwrap_or(Bounds::from_positions(&[]))
+            }
+        }
+    }
+
+    /// Hands `model` to the renderer. A material read from the file also sets up the
+    /// shading model, where it has matching parameters, unless the file is `reload`ed
+    /// and the settings may have been tweaked since.
+    fn upload(
+        &mut self,
         render_state: &egui_wgpu::RenderState,
-        path: &Option<&std::path::Path>,
-    ) -> (wgpu::Buffer, u32, Option<wgpu::TextureView>, Option<wgpu::Sampler>) {
-        let path = path.unwrap_or(std::path::Path::new(DEFAULT_OBJECT_PATH));
-        let (models, obj_materials) = tobj::load_obj(
-            path,
-            &tobj::LoadOptions {
-                triangulate: true,
-                single_index: true,
-                ..Default::default()
-            },
-        )
-        .expect("Failed to load OBJ file");
-
-        let mut texture_view = None;
-        let mut texture_sampler = None;
-
-        if let Ok(materials) = obj_materials {
-            if let Some(mat) = materials.first() {
-                if let Some(texture) = &mat.diffuse_texture {
-                    eprintln!("{texture:?}");
-                    let img = image::open(texture).expect("no image");
-                    let rgba = img.to_rgba8();
-                    let size = wgpu::Extent3d {
-                        width: rgba.width(),
-                        height: rgba.height(),
-                        depth_or_array_layers: 1,
-                    };
-                    let texture = render_state.device.create_texture(&wgpu::TextureDescriptor {
-                        label: None,
-                        size,
-                        mip_level_count: 1,
-                        sample_count: 1,
-                        dimension: wgpu::TextureDimension::D2,
-                        format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                        view_formats: &[],
-                    });
-                    render_state.queue.write_texture(
-                        wgpu::TexelCopyTextureInfo {
-                            texture: &texture,
-                            mip_level: 0,
-                            origin: wgpu::Origin3d::ZERO,
-                            aspect: wgpu::TextureAspect::All,
-                        },
-                        &rgba, 
-                        wgpu::TexelCopyBufferLayout {
-                            offset: 0,
-                            bytes_per_row: Some(4 * rgba.width()),
-                            rows_per_image: Some(rgba.height()),
-                        },
-                        size
+        model: &Model,
+        reload: bool,
+    ) -> Bounds {
+        {
+            let mut shading_model = self.shading_model.lock().unwrap();
+            if !reload
+                && let Some((metallic, roughness)) = model
+                    .parts
+                    .iter()
+                    .find_map(|(_, material)| material.metallic_roughness)
+            {
+                shading_model.set_metallic_roughness(metallic, roughness);
+            }
+            crate::renderer::load_object(render_state, self.id, model, &*shading_model);
+        }
+
+        self.files = model.files.clone();
+        self.update_watcher();
+        let bounds = model.bounds();
+        self.bounds = Some(bounds);
+        bounds
+    }
+
+    /// Follows the files of the current mesh while `watch` is on.
+    fn update_watcher(&mut self) {
+        self.watcher = None;
+        if !self.watch || self.files.is_empty() {
+            return;
+        }
+        match FileWatcher::new(&self.files) {
+            Ok(watcher) => self.watcher = Some(watcher),
+            Err(error) => self.load_error = Some(format!("Couldn't watch the file: {error}")),
+        }
+    }
+
+    /// Starts reading `source` on a background thread, dropping any load in progress.
+    fn start_loading(&mut self, source: MeshSource, reload: bool) {
+        let thread = std::thread::spawn({
+            let source = source.clone();
+            move || Self::read(&source)
+        });
+        self.loading = Some(Loading {
+            source,
+            reload,
+            started: Instant::now(),
+            thread,
+        });
+        self.load_error = None;
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    /// Starts reloading the file when the watcher saw it change, and uploads the mesh
+    /// once the background thread has read it, switching `source` to it.
+    pub fn poll_loading(&mut self, render_state: &egui_wgpu::RenderState) -> Option<MeshEdit> {
+        if self.loading.is_none()
+            && let Some(watcher) = &mut self.watcher
+            && watcher.poll()
+        {
+            self.start_loading(self.source.clone(), true);
+        }
+
+        if !self.loading.as_ref()?.thread.is_finished() {
+            return None;
+        }
+        let loading = self.loading.take()?;
+        match loading.thread.join() {
+            Ok(Ok(model)) => {
+                self.source = loading.source;
+                self.upload(render_state, &model, loading.reload);
+                Some(if loading.reload {
+                    MeshEdit::Tweaked
+                } else {
+                    MeshEdit::Replaced
+                })
+            }
+            Ok(Err(error)) => {
+                self.load_error = Some(format!("Couldn't load the file: {error}"));
+                None
+            }
+            // A bug in a loader, or in a library it calls, rather than a bad file.
+            Err(panic) => {
+                let reason = panic
+                    .downcast_ref::<String>()
+                    .map(String::as_str)
+                    .or_else(|| panic.downcast_ref::<&str>().copied())
+                    .unwrap_or("unknown error");
+                self.load_error = Some(format!("Loading the file crashed: {reason}"));
+                None
+            }
+        }
+    }
+
+    pub fn build_widget(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) -> Option<MeshEdit> {
+        let mut edit = None;
+        let selected_text = match &self.source {
+            MeshSource::File(_) => "Model file".to_string(),
+            MeshSource::Primitive(primitive) => primitive.to_string(),
+        };
+        egui::ComboBox::from_label("Mesh")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                for primitive in Primitive::ALL {
+                    let selected = matches!(
+                        &self.source,
+                        MeshSource::Primitive(current) if current.same_kind(&primitive)
                     );
-                    texture_view = Some(texture.create_view(&wgpu::TextureViewDescriptor::default()));
-                    texture_sampler = Some(render_state.device.create_sampler(&wgpu::SamplerDescriptor {
-                        address_mode_u: wgpu::AddressMode::ClampToEdge,
-                        address_mode_v: wgpu::AddressMode::ClampToEdge,
-                        address_mode_w: wgpu::AddressMode::ClampToEdge,
-                        mag_filter: wgpu::FilterMode::Linear,
-                        min_filter: wgpu::FilterMode::Nearest,
-                        mipmap_filter: wgpu::FilterMode::Nearest,
-                        ..Default::default()
-                    }));
+                    if ui
+                        .selectable_label(selected, primitive.to_string())
+                        .clicked()
+                        && !selected
+                    {
+                        self.source = MeshSource::Primitive(primitive);
+                        self.loading = None;
+                        edit = Some(MeshEdit::Replaced);
+                    }
                 }
-            }
+            });
+        if let MeshSource::Primitive(primitive) = &mut self.source
+            && primitive.build_widget(ui)
+        {
+            edit = edit.or(Some(MeshEdit::Tweaked));
         }
 
-        let mesh = &models[0].mesh;
-
-        let mut vertices = Vec::new();
-        for i in &mesh.indices {
-            let i = *i as usize;
-            let position = [
-                mesh.positions[i * 3],
-                mesh.positions[i * 3 + 1],
-                mesh.positions[i * 3 + 2],
-            ];
-            let normal = if !mesh.normals.is_empty() {
-                [
-                    mesh.normals[i * 3],
-                    mesh.normals[i * 3 + 1],
-                    mesh.normals[i * 3 + 2],
-                ]
-            } else {
-                [0.0, 0.0, 1.0]
-            };
-            let texcoord = if !mesh.texcoords.is_empty() {
-                [mesh.texcoords[i * 2], mesh.texcoords[i * 2 + 1]]
-            } else {
-                [0.0, 0.0]
-            };
-            vertices.push(crate::renderer::Vertex::new(position, normal, texcoord));
-        }
-
-        let vertex_buffer =
-            render_state
-                .device
-                .create_buffer_init(&eframe::wgpu::util::BufferInitDescriptor {
-                    label: None,
-                    contents: bytemuck::cast_slice(&vertices),
-                    usage: eframe::wgpu::BufferUsages::VERTEX,
-                });
-        (vertex_buffer, vertices.len() as u32, texture_view, texture_sampler)
-    }
-
-    pub fn update_obj(render_state: &egui_wgpu::RenderState, path: &Option<&std::path::Path>) {
-        let path = path.unwrap_or(std::path::Path::new(DEFAULT_OBJECT_PATH));
-        let (models, _materials) = tobj::load_obj(
-            path,
-            &tobj::LoadOptions {
-                triangulate: true,
-                single_index: true,
-                ..Default::default()
-            },
-        )
-        .expect("Failed to load OBJ file");
-        let mesh = &models[0].mesh;
-
-        let mut vertices = Vec::new();
-        for i in &mesh.indices {
-            let i = *i as usize;
-            let position = [
-                mesh.positions[i * 3],
-                mesh.positions[i * 3 + 1],
-                mesh.positions[i * 3 + 2],
-            ];
-            let normal = if !mesh.normals.is_empty() {
-                [
-                    mesh.normals[i * 3],
-                    mesh.normals[i * 3 + 1],
-                    mesh.normals[i * 3 + 2],
-                ]
-            } else {
-                [0.0, 0.0, 1.0]
-            };
-            let texcoord = if !mesh.texcoords.is_empty() {
-                [mesh.texcoords[i * 2], mesh.texcoords[i * 2 + 1]]
-            } else {
-                [0.0, 0.0]
-            };
-            vertices.push(crate::renderer::Vertex::new(position, normal, texcoord));
-        }
-
-        let vertex_buffer =
-            render_state
-                .device
-                .create_buffer_init(&eframe::wgpu::util::BufferInitDescriptor {
-                    label: None,
-                    contents: bytemuck::cast_slice(&vertices),
-                    usage: eframe::wgpu::BufferUsages::VERTEX,
-                });
-        render_state
-            .renderer
-            .write()
-            .callback_resources
-            .get_mut::<ObjectRenderResources>()
-            .unwrap()
-            .set_vertex_buffer(vertex_buffer, vertices.len() as u32);
-    }
-
-    pub fn build_widget(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) -> bool {
         if (ui.button("Load 3D model")).clicked() {
-            // Show only files with the extension "obj".
-            let filter = Box::new({
-                let ext = Some(OsStr::new("obj"));
-                move |path: &Path| -> bool { path.extension() == ext }
+            // Show only the formats `Model::load` reads.
+            let filter = Box::new(|path: &Path| -> bool {
+                path.extension().and_then(OsStr::to_str).is_some_and(|ext| {
+                    MODEL_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str())
+                })
             });
             let mut dialog =
-                FileDialog::open_file(self.opened_file.clone()).show_files_filter(filter);
+                FileDialog::open_file(self.path().map(Path::to_path_buf)).show_files_filter(filter);
             dialog.open();
             self.open_file_dialog = Some(dialog);
         }
-
-        if let Some(dialog) = &mut self.open_file_dialog {
-            if dialog.show(ctx).selected() {
-                if let Some(file) = dialog.path() {
-                    self.opened_file = Some(file.to_path_buf());
-                    return true;
-                }
+        if matches!(self.source, MeshSource::File(_))
+            && ui
+                .checkbox(&mut self.watch, "Reload when the file changes")
+                .on_hover_text("Also watches its materials and textures")
+                .changed()
+        {
+            self.update_watcher();
+        }
+        if let Some(loading) = &self.loading {
+            let mut cancel = false;
+            ui.horizontal(|ui| {
+                ui.spinner();
+                let name = match &loading.source {
+                    MeshSource::File(path) => path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .into_owned(),
+                    MeshSource::Primitive(primitive) => primitive.to_string(),
+                };
+                ui.label(format!(
+                    "Loading {name}, {:.1}s",
+                    loading.started.elapsed().as_secs_f32()
+                ));
+                // The thread can't be stopped; whatever it reads is thrown away.
+                cancel = ui.button("Cancel").clicked();
+            });
+            if cancel {
+                self.loading = None;
             }
         }
-        false
+        if let Some(error) = &self.load_error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        self.transform.build_widget(ui);
+        if let Some(bounds) = &self.bounds {
+            let size = bounds.max - bounds.min;
+            ui.label(format!(
+                "size: {:.3} x {:.3} x {:.3}, radius {:.3}",
+                size.x, size.y, size.z, bounds.radius
+            ));
+        }
+
+        if let Some(dialog) = &mut self.open_file_dialog
+            && dialog.show(ctx).selected()
+            && let Some(file) = dialog.path()
+        {
+            let source = MeshSource::File(file.to_path_buf());
+            self.start_loading(source, false);
+        }
+        edit
     }
 }
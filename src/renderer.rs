@@ -1,82 +1,151 @@
+pub mod graph;
+pub mod inspect;
+pub mod pick;
+
 use eframe::{
     egui_wgpu::{self, wgpu},
     wgpu::util::DeviceExt,
 };
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use wgpu::PipelineCompilationOptions;
 
-use crate::config::{self, PostEffect, ShadingModel};
+use crate::config::{self, PostEffectEntry, ShadingModel, pipeline::PipelineSettings};
+use graph::{GraphTexture, GraphTextureDesc, RenderGraph, TexturePool};
 
-pub fn build_pipeline(
-    render_state: &egui_wgpu::RenderState,
-    path: &Option<&std::path::Path>,
-    shading_model: &(impl config::ShadingModel + ?Sized),
+/// Format of the view-space normal buffer written by the scene pass.
+pub const NORMAL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Size of one effect's slot in the shared post params buffer, also its dynamic offset step.
+pub const POST_PARAMS_SIZE: u64 = 256;
+
+/// Stride of the per-object slots in the model buffer, the minimum uniform buffer
+/// offset alignment.
+const MODEL_UNIFORM_SIZE: u64 = 256;
+
+/// Render graph textures written by the scene pass.
+pub const SCENE_COLOR: &str = "scene_color";
+pub const SCENE_NORMALS: &str = "scene_normals";
+pub const SCENE_DEPTH: &str = "scene_depth";
+
+/// Render graph texture written by the `index`-th enabled post effect.
+pub fn post_output_name(index: usize) -> String {
+    format!("post_{index}")
+}
+
+/// Render graph texture holding the history the `index`-th enabled post effect reads,
+/// written only while the texture viewer shows it.
+pub fn post_history_name(index: usize) -> String {
+    format!("post_{index}_history")
+}
+
+/// Copies a post effect's history into a graph texture for the texture viewer.
+fn copy_history(
+    encoder: &mut wgpu::CommandEncoder,
+    history: Option<&wgpu::Texture>,
+    target: &GraphTexture,
 ) {
-    let src = shading_model.get_source();
+    match history {
+        Some(history)
+            if history.size() == target.texture.size()
+                && history.format() == target.texture.format() =>
+        {
+            encoder.copy_texture_to_texture(
+                history.as_image_copy(),
+                target.texture.as_image_copy(),
+                target.texture.size(),
+            );
+        }
+        // No history yet: show black rather than whatever the pooled texture held.
+        _ => {
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("history"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+        }
+    }
+}
+
+/// Blends a post effect's output over its input by the pass blend constant (the entry's mix).
+const POST_MIX_BLEND: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Constant,
+        dst_factor: wgpu::BlendFactor::OneMinusConstant,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Constant,
+        dst_factor: wgpu::BlendFactor::OneMinusConstant,
+        operation: wgpu::BlendOperation::Add,
+    },
+};
+
+pub fn build_pipeline(render_state: &egui_wgpu::RenderState, settings: &PipelineSettings) {
     let device = &render_state.device;
-    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: None,
-        source: wgpu::ShaderSource::Wgsl(src.into()),
-    });
 
-    let (camera_bind_group_layout, camera_bind_group, camera_buffer) =
-        CameraUniform::create_uniform(device);
+    let (camera_bind_group_layout, camera_buffer) = CameraUniform::create_uniform(device);
+    let (model_buffer, camera_bind_group) =
+        create_model_uniforms(device, &camera_bind_group_layout, &camera_buffer, 1);
 
     let (light_bind_group_layout, light_bind_group, light_buffer) =
         LightUniform::create_uniform(device);
 
-    let (params_bind_group_layout, params_bind_group, params_buffer) =
-        shading_model.create_uniform(device);
-
-    let (vertex_buffer, vertex_count, mut texture_view, mut texture_sampler) = 
-        crate::object::Object::load_obj(render_state, path);
-
-    if texture_view.is_none() {
-        let img_bytes = include_bytes!("proxy-image.jpg");
-        let img = image::load_from_memory(img_bytes).unwrap();
-        let rgba = img.to_rgba8();
-        let size = wgpu::Extent3d {
-            width: rgba.width(),
-            height: rgba.height(),
-            depth_or_array_layers: 1,
-        };
-        let texture = render_state.device.create_texture(&wgpu::TextureDescriptor {
-            label: None,
-            size,
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
-        });
-        render_state.queue.write_texture(
-            wgpu::TexelCopyTextureInfo {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            &rgba, 
-            wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * rgba.width()),
-                rows_per_image: Some(rgba.height()),
-            },
-            size
-        );
-        texture_view = Some(texture.create_view(&wgpu::TextureViewDescriptor::default()));
-        texture_sampler = Some(render_state.device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        }));
-    }
+    let img_bytes = include_bytes!("proxy-image.jpg");
+    let img = image::load_from_memory(img_bytes).unwrap();
+    let rgba = img.to_rgba8();
+    let size = wgpu::Extent3d {
+        width: rgba.width(),
+        height: rgba.height(),
+        depth_or_array_layers: 1,
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    render_state.queue.write_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        &rgba,
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * rgba.width()),
+            rows_per_image: Some(rgba.height()),
+        },
+        size,
+    );
+    let proxy_texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let proxy_texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Nearest,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        ..Default::default()
+    });
 
     let texture_bind_group_layout =
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -103,35 +172,213 @@ pub fn build_pipeline(
             label: Some("texture_bind_group_layout"),
         });
 
-    let texture_bind_group = device.create_bind_group(
-        &wgpu::BindGroupDescriptor {
-            layout: &texture_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&texture_view.as_ref().unwrap()),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&texture_sampler.as_ref().unwrap()),
-                }
-            ],
-            label: Some("diffuse_bind_group"),
-        }
-    );
-
-    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        label: None,
-        bind_group_layouts: &[
+    let debug_view = DebugViewResources::new(
+        device,
+        render_state.target_format,
+        &[
             &camera_bind_group_layout,
             &light_bind_group_layout,
-            &params_bind_group_layout,
             &texture_bind_group_layout,
         ],
+        settings,
+    );
+    let wireframe = WireframeResources::new(
+        device,
+        render_state.target_format,
+        &camera_bind_group_layout,
+        settings,
+    );
+
+    render_state
+        .renderer
+        .write()
+        .callback_resources
+        .insert(ObjectRenderResources {
+            pipelines: HashMap::new(),
+            settings: *settings,
+            target_format: render_state.target_format,
+            camera_bind_group,
+            camera_buffer,
+            model_buffer,
+            model_capacity: 1,
+            light_buffer,
+            light_bind_group,
+            objects: HashMap::new(),
+            post_process_resources: None,
+            proxy_texture_view,
+            proxy_texture_sampler,
+            prev_view_proj: None,
+            inspector: None,
+            picker: pick::DepthPicker::new(device),
+            debug_view,
+            wireframe,
+            camera_bind_group_layout,
+            light_bind_group_layout,
+            texture_bind_group_layout,
+        });
+}
+
+/// Uploads `model` as scene object `id`, replacing what was loaded under that id
+/// before.
+pub fn load_object(
+    render_state: &egui_wgpu::RenderState,
+    id: u64,
+    model: &crate::mesh::Model,
+    shading_model: &(impl config::ShadingModel + ?Sized),
+) {
+    let device = &render_state.device;
+    let (vertex_buffer, parts) = model.upload(device, &render_state.queue);
+    let (params_layout, params_bind_group, params_buffer) = shading_model.create_uniform(device);
+
+    let mut renderer = render_state.renderer.write();
+    let resources = renderer
+        .callback_resources
+        .get_mut::<ObjectRenderResources>()
+        .unwrap();
+
+    let parts = parts
+        .into_iter()
+        .map(|(vertices, texture)| {
+            let (texture_view, texture_sampler) = match &texture {
+                Some((view, sampler)) => (view, sampler),
+                None => (
+                    &resources.proxy_texture_view,
+                    &resources.proxy_texture_sampler,
+                ),
+            };
+            let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &resources.texture_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(texture_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(texture_sampler),
+                    },
+                ],
+                label: Some("diffuse_bind_group"),
+            });
+            SceneObjectPart {
+                vertices,
+                texture_bind_group,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    resources.objects.insert(
+        id,
+        SceneObjectResources {
+            shading_model: shading_model.as_enum(),
+            params_layout,
+            params_buffer,
+            params_bind_group,
+            vertex_count: parts.last().map_or(0, |part| part.vertices.end),
+            vertex_buffer,
+            parts,
+        },
+    );
+}
+
+/// Gives object `id` fresh params for `shading_model`, keeping its mesh.
+pub fn set_object_shading_model(
+    render_state: &egui_wgpu::RenderState,
+    id: u64,
+    shading_model: &(impl config::ShadingModel + ?Sized),
+) {
+    let (params_layout, params_bind_group, params_buffer) =
+        shading_model.create_uniform(&render_state.device);
+
+    let mut renderer = render_state.renderer.write();
+    if let Some(object) = renderer
+        .callback_resources
+        .get_mut::<ObjectRenderResources>()
+        .and_then(|resources| resources.objects.get_mut(&id))
+    {
+        object.shading_model = shading_model.as_enum();
+        object.params_layout = params_layout;
+        object.params_buffer = params_buffer;
+        object.params_bind_group = params_bind_group;
+    }
+}
+
+pub fn remove_object(render_state: &egui_wgpu::RenderState, id: u64) {
+    if let Some(resources) = render_state
+        .renderer
+        .write()
+        .callback_resources
+        .get_mut::<ObjectRenderResources>()
+    {
+        resources.objects.remove(&id);
+    }
+}
+
+/// Applies `settings` to an already built scene, keeping the loaded objects and post
+/// processing resources. Shading model pipelines are rebuilt as they're next drawn.
+pub fn rebuild_pipeline(render_state: &egui_wgpu::RenderState, settings: &PipelineSettings) {
+    let device = &render_state.device;
+    let target_format = render_state.target_format;
+
+    let mut renderer = render_state.renderer.write();
+    let resources = renderer
+        .callback_resources
+        .get_mut::<ObjectRenderResources>()
+        .unwrap();
+
+    resources.pipelines.clear();
+    resources.settings = *settings;
+    resources.debug_view = DebugViewResources::new(
+        device,
+        target_format,
+        &[
+            &resources.camera_bind_group_layout,
+            &resources.light_bind_group_layout,
+            &resources.texture_bind_group_layout,
+        ],
+        settings,
+    );
+    resources.wireframe = WireframeResources::new(
+        device,
+        target_format,
+        &resources.camera_bind_group_layout,
+        settings,
+    );
+}
+
+fn scene_primitive_state(settings: &PipelineSettings) -> wgpu::PrimitiveState {
+    wgpu::PrimitiveState {
+        topology: wgpu::PrimitiveTopology::TriangleList,
+        strip_index_format: None,
+        front_face: settings.front_face,
+        cull_mode: settings.cull_mode,
+        unclipped_depth: false,
+        polygon_mode: settings.polygon_mode,
+        conservative: false,
+    }
+}
+
+/// The shading model's pipeline. `layouts` are the camera, light, shading model
+/// params and model texture layouts, in group order.
+fn create_scene_pipeline(
+    device: &wgpu::Device,
+    target_format: wgpu::TextureFormat,
+    src: String,
+    layouts: &[&wgpu::BindGroupLayout; 4],
+    settings: &PipelineSettings,
+) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: None,
+        source: wgpu::ShaderSource::Wgsl(src.into()),
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: None,
+        bind_group_layouts: layouts,
         push_constant_ranges: &[],
     });
 
-    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
         label: None,
         layout: Some(&pipeline_layout),
         vertex: wgpu::VertexState {
@@ -143,18 +390,10 @@ pub fn build_pipeline(
         fragment: Some(wgpu::FragmentState {
             module: &shader,
             entry_point: Some("fs_main"),
-            targets: &[Some(render_state.target_format.into())],
+            targets: &[Some(target_format.into()), Some(NORMAL_FORMAT.into())],
             compilation_options: PipelineCompilationOptions::default(),
         }),
-        primitive: wgpu::PrimitiveState {
-            topology: wgpu::PrimitiveTopology::TriangleList,
-            strip_index_format: None,
-            front_face: wgpu::FrontFace::Cw,
-            cull_mode: None,
-            unclipped_depth: false,
-            polygon_mode: wgpu::PolygonMode::Fill,
-            conservative: false,
-        },
+        primitive: scene_primitive_state(settings),
         depth_stencil: Some(wgpu::DepthStencilState {
             format: wgpu::TextureFormat::Depth32Float,
             depth_write_enabled: true,
@@ -165,151 +404,304 @@ pub fn build_pipeline(
         multisample: wgpu::MultisampleState::default(),
         multiview: None,
         cache: None,
-    });
-
-    
+    })
+}
 
-    render_state
-        .renderer
-        .write()
-        .callback_resources
-        .insert(ObjectRenderResources {
-            pipeline,
-            camera_bind_group,
-            camera_buffer,
-            light_buffer,
-            light_bind_group,
-            params_buffer,
-            params_bind_group,
-            vertex_buffer,
-            vertex_count,
-            post_process_resources: None,
-            _model_texture_view: texture_view.unwrap(),
-            _model_texture_sampler: texture_sampler.unwrap(),
-            model_texture_bind_group: texture_bind_group
-        });
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct DebugViewUniform {
+    mode: u32,
+    _padding: [u32; 3],
 }
 
-pub struct PostProcessResources {
-    depth_texture_view: wgpu::TextureView,
-    _depth_sampler: wgpu::Sampler,
-    target_format: wgpu::TextureFormat,
-    texture_view_a: wgpu::TextureView,
-    texture_view_b: wgpu::TextureView,
-    bind_group_a: wgpu::BindGroup,
-    bind_group_b: wgpu::BindGroup,
+/// Pipelines for the scene pass debug view modes, drawn with `debug_view.wgsl`
+/// in place of the shading model.
+struct DebugViewResources {
     pipeline: wgpu::RenderPipeline,
-    vertex_buffer: wgpu::Buffer,
-    out: PostProcessTexture,
+    /// Additive, without depth testing, so every covered fragment counts.
+    overdraw_pipeline: wgpu::RenderPipeline,
+    params_buffer: wgpu::Buffer,
+    params_bind_group: wgpu::BindGroup,
 }
 
-#[derive(Clone, Copy)]
-enum PostProcessTexture {
-    A,
-    B,
-}
+impl DebugViewResources {
+    /// `layouts` are the camera, light and model texture layouts of the scene pipeline.
+    fn new(
+        device: &wgpu::Device,
+        target_format: wgpu::TextureFormat,
+        layouts: &[&wgpu::BindGroupLayout; 3],
+        settings: &PipelineSettings,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("debug_view"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("debug_view.wgsl").into()),
+        });
 
-impl PostProcessResources {
-    fn swap_buffers(&mut self) {
-        self.out = match self.out {
-            PostProcessTexture::A => PostProcessTexture::B,
-            PostProcessTexture::B => PostProcessTexture::A,
+        let params_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("debug_view"),
+        });
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("debug_view"),
+            size: std::mem::size_of::<DebugViewUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let params_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &params_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buffer.as_entire_binding(),
+            }],
+            label: Some("debug_view"),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("debug_view"),
+            bind_group_layouts: &[layouts[0], layouts[1], &params_layout, layouts[2]],
+            push_constant_ranges: &[],
+        });
+
+        let create = |overdraw: bool| {
+            let additive = wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            };
+            let (color_target, normal_target) = if overdraw {
+                (
+                    wgpu::ColorTargetState {
+                        format: target_format,
+                        blend: Some(wgpu::BlendState {
+                            color: additive,
+                            alpha: wgpu::BlendComponent::REPLACE,
+                        }),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    },
+                    wgpu::ColorTargetState {
+                        format: NORMAL_FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::empty(),
+                    },
+                )
+            } else {
+                (target_format.into(), NORMAL_FORMAT.into())
+            };
+
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("debug_view"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[Vertex::desc()],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(color_target), Some(normal_target)],
+                    compilation_options: PipelineCompilationOptions::default(),
+                }),
+                primitive: scene_primitive_state(settings),
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth32Float,
+                    depth_write_enabled: !overdraw,
+                    depth_compare: if overdraw {
+                        wgpu::CompareFunction::Always
+                    } else {
+                        wgpu::CompareFunction::Less
+                    },
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
         };
-    }
 
-    fn get_texture_out_view(&self) -> &wgpu::TextureView {
-        match self.out {
-            PostProcessTexture::A => &self.texture_view_a,
-            PostProcessTexture::B => &self.texture_view_b,
+        Self {
+            pipeline: create(false),
+            overdraw_pipeline: create(true),
+            params_buffer,
+            params_bind_group,
         }
     }
 
-    fn get_bind_group_in(&self) -> &wgpu::BindGroup {
-        match self.out {
-            PostProcessTexture::A => &self.bind_group_b,
-            PostProcessTexture::B => &self.bind_group_a,
-        }
+    fn write_mode(&self, queue: &wgpu::Queue, mode: config::ViewMode) {
+        let params = DebugViewUniform {
+            mode: mode as u32,
+            _padding: [0; 3],
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
     }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct WireframeUniform {
+    color: [f32; 4],
+    width: f32,
+    _padding: [u32; 3],
+}
 
-    pub fn get_final_bind_group(&self) -> &wgpu::BindGroup {
-        match self.out {
-            PostProcessTexture::A => &self.bind_group_a,
-            PostProcessTexture::B => &self.bind_group_b,
+impl WireframeUniform {
+    pub fn new(color: [f32; 4], width: f32) -> Self {
+        Self {
+            color,
+            width,
+            _padding: [0; 3],
         }
     }
 }
 
-pub fn post_effect_init(render_state: &egui_wgpu::RenderState, size: (u32, u32)) {
-    let device = &render_state.device;
-    let target_format = render_state.target_format;
+/// Edge overlay drawn over the shaded scene from barycentrics, so it doesn't need
+/// line polygon mode support.
+struct WireframeResources {
+    pipeline: wgpu::RenderPipeline,
+    params_buffer: wgpu::Buffer,
+    params_bind_group: wgpu::BindGroup,
+}
 
-    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: None,
-        source: wgpu::ShaderSource::Wgsl(include_str!("post.wgsl").into()),
-    });
+impl WireframeResources {
+    fn new(
+        device: &wgpu::Device,
+        target_format: wgpu::TextureFormat,
+        camera_layout: &wgpu::BindGroupLayout,
+        settings: &PipelineSettings,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("wireframe"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("wireframe.wgsl").into()),
+        });
 
-    let tex_desc = &wgpu::TextureDescriptor {
-        label: Some("post_process_texture"),
-        size: wgpu::Extent3d {
-            width: size.0,
-            height: size.1,
-            depth_or_array_layers: 1,
-        },
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: target_format,
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-        view_formats: &[target_format],
-    };
+        let params_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("wireframe"),
+        });
 
-    let depth_desc = &wgpu::TextureDescriptor {
-        label: None,
-        size: wgpu::Extent3d {
-            width: size.0,
-            height: size.1,
-            depth_or_array_layers: 1,
-        },
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Depth32Float,
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-        view_formats: &[],
-    };
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("wireframe"),
+            size: std::mem::size_of::<WireframeUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
 
-    let texture_a = device.create_texture(tex_desc);
-    let texture_b = device.create_texture(tex_desc);
-    let depth_texture = device.create_texture(depth_desc);
+        let params_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &params_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buffer.as_entire_binding(),
+            }],
+            label: Some("wireframe"),
+        });
 
-    let depth_texture_view = depth_texture.create_view(&Default::default());
-    let texture_view_a = texture_a.create_view(&Default::default());
-    let texture_view_b = texture_b.create_view(&Default::default());
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("wireframe"),
+            bind_group_layouts: &[camera_layout, &params_layout],
+            push_constant_ranges: &[],
+        });
 
-    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-        address_mode_u: wgpu::AddressMode::ClampToEdge,
-        address_mode_v: wgpu::AddressMode::ClampToEdge,
-        address_mode_w: wgpu::AddressMode::ClampToEdge,
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Nearest,
-        mipmap_filter: wgpu::FilterMode::Nearest,
-        ..Default::default()
-    });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("wireframe"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[
+                    Some(wgpu::ColorTargetState {
+                        format: target_format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }),
+                    Some(wgpu::ColorTargetState {
+                        format: NORMAL_FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::empty(),
+                    }),
+                ],
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                polygon_mode: wgpu::PolygonMode::Fill,
+                ..scene_primitive_state(settings)
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            params_buffer,
+            params_bind_group,
+        }
+    }
+}
 
-    let _depth_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-        address_mode_u: wgpu::AddressMode::ClampToEdge,
-        address_mode_v: wgpu::AddressMode::ClampToEdge,
-        address_mode_w: wgpu::AddressMode::ClampToEdge,
-        mag_filter: wgpu::FilterMode::Linear,
-        min_filter: wgpu::FilterMode::Linear,
-        mipmap_filter: wgpu::FilterMode::Nearest,
-        compare: Some(wgpu::CompareFunction::LessEqual),
-        lod_min_clamp: 0.0,
-        lod_max_clamp: 100.0,
-        ..Default::default()
-    });
+pub struct PostProcessResources {
+    pool: TexturePool,
+    _depth_sampler: wgpu::Sampler,
+    target_format: wgpu::TextureFormat,
+    frame_buffer: wgpu::Buffer,
+    params_buffer: wgpu::Buffer,
+    params_bind_group: wgpu::BindGroup,
+    params_capacity: u64,
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    /// What the last graph run left for `paint` to blit.
+    output: Option<GraphTexture>,
+}
+
+impl PostProcessResources {
+    /// Makes sure the params buffer has a slot for each of `count` effects.
+    fn reserve_params(&mut self, device: &wgpu::Device, count: u64) {
+        if count <= self.params_capacity {
+            return;
+        }
+        let (params_buffer, params_bind_group) = create_post_params(device, count);
+        self.params_buffer = params_buffer;
+        self.params_bind_group = params_bind_group;
+        self.params_capacity = count;
+    }
+}
 
-    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+fn post_input_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
         entries: &[
             wgpu::BindGroupLayoutEntry {
                 binding: 0,
@@ -329,38 +721,176 @@ pub fn post_effect_init(render_state: &egui_wgpu::RenderState, size: (u32, u32))
             },
         ],
         label: None,
-    });
+    })
+}
 
-    let bind_group_a = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        layout: &bind_group_layout,
+fn post_scene_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
         entries: &[
-            wgpu::BindGroupEntry {
+            wgpu::BindGroupLayoutEntry {
                 binding: 0,
-                resource: wgpu::BindingResource::TextureView(&texture_view_a),
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Depth,
+                },
+                count: None,
             },
-            wgpu::BindGroupEntry {
+            wgpu::BindGroupLayoutEntry {
                 binding: 1,
-                resource: wgpu::BindingResource::Sampler(&sampler),
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(
+                        std::mem::size_of::<CameraUniform>() as u64
+                    ),
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(
+                        std::mem::size_of::<FrameUniform>() as u64
+                    ),
+                },
+                count: None,
             },
         ],
-        label: None,
+        label: Some("post_scene_bind_group_layout"),
+    })
+}
+
+fn post_params_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: true,
+                min_binding_size: wgpu::BufferSize::new(POST_PARAMS_SIZE),
+            },
+            count: None,
+        }],
+        label: Some("post_params_bind_group_layout"),
+    })
+}
+
+fn create_post_params(device: &wgpu::Device, count: u64) -> (wgpu::Buffer, wgpu::BindGroup) {
+    let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("post_params"),
+        size: POST_PARAMS_SIZE * count.max(1),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let params_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &post_params_bind_group_layout(device),
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer: &params_buffer,
+                offset: 0,
+                size: wgpu::BufferSize::new(POST_PARAMS_SIZE),
+            }),
+        }],
+        label: Some("post_params"),
     });
 
-    let bind_group_b = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        layout: &bind_group_layout,
+    (params_buffer, params_bind_group)
+}
+
+fn create_post_scene_bind_group(
+    device: &wgpu::Device,
+    depth_view: &wgpu::TextureView,
+    normal_view: &wgpu::TextureView,
+    camera_buffer: &wgpu::Buffer,
+    frame_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &post_scene_bind_group_layout(device),
         entries: &[
             wgpu::BindGroupEntry {
                 binding: 0,
-                resource: wgpu::BindingResource::TextureView(&texture_view_b),
+                resource: wgpu::BindingResource::TextureView(depth_view),
             },
             wgpu::BindGroupEntry {
                 binding: 1,
-                resource: wgpu::BindingResource::Sampler(&sampler),
+                resource: wgpu::BindingResource::TextureView(normal_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: camera_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: frame_buffer.as_entire_binding(),
             },
         ],
+        label: Some("post_scene"),
+    })
+}
+
+pub fn post_effect_init(render_state: &egui_wgpu::RenderState, size: (u32, u32)) {
+    let device = &render_state.device;
+    let target_format = render_state.target_format;
+
+    let mut renderer = render_state.renderer.write();
+    let resources = renderer
+        .callback_resources
+        .get_mut::<ObjectRenderResources>()
+        .unwrap();
+
+    if let Some(post) = &mut resources.post_process_resources {
+        post.pool.resize(size);
+        return;
+    }
+
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label: None,
+        source: wgpu::ShaderSource::Wgsl(include_str!("post.wgsl").into()),
+    });
+
+    let _depth_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        compare: Some(wgpu::CompareFunction::LessEqual),
+        lod_min_clamp: 0.0,
+        lod_max_clamp: 100.0,
+        ..Default::default()
+    });
+
+    let bind_group_layout = post_input_bind_group_layout(device);
+
+    let frame_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("post_frame"),
+        size: std::mem::size_of::<FrameUniform>() as u64,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
     });
 
+    let (params_buffer, params_bind_group) = create_post_params(device, 1);
+
     let quad_vertices: &[PostVertex] = &[
         PostVertex::new([-1.0, 1.0], [0.0, 0.0]),
         PostVertex::new([1.0, 1.0], [1.0, 0.0]),
@@ -413,62 +943,50 @@ pub fn post_effect_init(render_state: &egui_wgpu::RenderState, size: (u32, u32))
     });
 
     let post_process_resources = PostProcessResources {
-        depth_texture_view,
+        pool: TexturePool::new(device, size),
         _depth_sampler,
         target_format,
-        texture_view_a,
-        texture_view_b,
-        bind_group_a,
-        bind_group_b,
+        frame_buffer,
+        params_buffer,
+        params_bind_group,
+        params_capacity: 1,
         pipeline,
         vertex_buffer,
-        out: PostProcessTexture::A,
+        output: None,
     };
 
-    render_state
-        .renderer
-        .write()
-        .callback_resources
-        .get_mut::<ObjectRenderResources>()
-        .unwrap()
-        .set_post_process_resources(post_process_resources);
+    resources.set_post_process_resources(post_process_resources);
 }
 
 pub fn create_post_pipeline(
     device: &wgpu::Device,
     target_format: wgpu::TextureFormat,
     src: String,
+) -> wgpu::RenderPipeline {
+    create_post_pipeline_with_layout(device, target_format, src, None)
+}
+
+/// Builds a post pipeline whose effect binds its own resources at group 3.
+pub fn create_post_pipeline_with_layout(
+    device: &wgpu::Device,
+    target_format: wgpu::TextureFormat,
+    src: String,
+    effect_layout: Option<&wgpu::BindGroupLayout>,
 ) -> wgpu::RenderPipeline {
     let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label: None,
         source: wgpu::ShaderSource::Wgsl(src.into()),
     });
 
-    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        entries: &[
-            wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Texture {
-                    multisampled: false,
-                    view_dimension: wgpu::TextureViewDimension::D2,
-                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                },
-                count: None,
-            },
-            wgpu::BindGroupLayoutEntry {
-                binding: 1,
-                visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                count: None,
-            },
-        ],
-        label: None,
-    });
+    let input_layout = post_input_bind_group_layout(device);
+    let scene_layout = post_scene_bind_group_layout(device);
+    let params_layout = post_params_bind_group_layout(device);
+    let mut bind_group_layouts = vec![&input_layout, &scene_layout, &params_layout];
+    bind_group_layouts.extend(effect_layout);
 
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: None,
-        bind_group_layouts: &[&bind_group_layout],
+        bind_group_layouts: &bind_group_layouts,
         push_constant_ranges: &[],
     });
 
@@ -484,7 +1002,11 @@ pub fn create_post_pipeline(
         fragment: Some(wgpu::FragmentState {
             module: &shader,
             entry_point: Some("fs_main"),
-            targets: &[Some(target_format.into())],
+            targets: &[Some(wgpu::ColorTargetState {
+                format: target_format,
+                blend: Some(POST_MIX_BLEND),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
             compilation_options: PipelineCompilationOptions::default(),
         }),
         primitive: wgpu::PrimitiveState {
@@ -503,11 +1025,27 @@ pub fn create_post_pipeline(
     })
 }
 
+/// One scene object as it should be drawn this frame.
+pub struct ObjectDraw {
+    /// The id its mesh was loaded under with [`load_object`].
+    pub id: u64,
+    pub model: ModelUniform,
+    pub shading_model: Arc<Mutex<dyn ShadingModel + Send>>,
+}
+
 pub struct ObjectRenderCallback {
     pub view_projection: CameraUniform,
+    /// The visible objects of the scene.
+    pub objects: Vec<ObjectDraw>,
     pub light: LightUniform,
-    pub shading_model: Arc<Mutex<dyn ShadingModel + Send>>,
-    pub post_effects: Vec<Arc<Mutex<dyn PostEffect + Send>>>,
+    pub post_effects: Vec<PostEffectEntry>,
+    pub frame: FrameUniform,
+    pub inspect: Option<inspect::InspectRequest>,
+    /// Viewport pixel whose depth to read back for [`pick::take_picked_point`].
+    pub pick: Option<[u32; 2]>,
+    pub view_mode: config::ViewMode,
+    /// Edge overlay over the scene, when enabled.
+    pub wireframe: Option<WireframeUniform>,
 }
 
 impl egui_wgpu::CallbackTrait for ObjectRenderCallback {
@@ -524,66 +1062,277 @@ impl egui_wgpu::CallbackTrait for ObjectRenderCallback {
             device,
             queue,
             &self.view_projection,
+            &self.objects,
             &self.light,
-            self.shading_model.clone(),
         );
         if let Some(post) = &mut resources.post_process_resources {
-            let mut encoder = device.create_command_encoder(&Default::default());
-            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: post.get_texture_out_view(),
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
-                        store: wgpu::StoreOp::Store,
+            let post_effects: Vec<&PostEffectEntry> = self
+                .post_effects
+                .iter()
+                .filter(|entry| entry.enabled)
+                .collect();
+            post.reserve_params(device, post_effects.len() as u64);
+            queue.write_buffer(&post.frame_buffer, 0, bytemuck::bytes_of(&self.frame));
+            let target_format = post.target_format;
+            let vertex_buffer = &post.vertex_buffer;
+            let params_buffer = &post.params_buffer;
+            let params_bind_group = &post.params_bind_group;
+            let frame_buffer = &post.frame_buffer;
+            let camera_buffer = &resources.camera_buffer;
+
+            let view_mode = self.view_mode;
+            resources.debug_view.write_mode(queue, view_mode);
+            if let Some(wireframe) = &self.wireframe {
+                queue.write_buffer(
+                    &resources.wireframe.params_buffer,
+                    0,
+                    bytemuck::bytes_of(wireframe),
+                );
+            }
+            // Overdraw adds up from black.
+            let clear_color = match view_mode {
+                config::ViewMode::Overdraw => wgpu::Color::BLACK,
+                _ => wgpu::Color::WHITE,
+            };
+
+            let mut draws: Vec<(u32, &SceneObjectResources)> = self
+                .objects
+                .iter()
+                .enumerate()
+                .filter_map(|(index, draw)| {
+                    let offset = (index as u64 * MODEL_UNIFORM_SIZE) as u32;
+                    Some((offset, resources.objects.get(&draw.id)?))
+                })
+                .collect();
+            // Objects sharing a shading model are drawn back to back, binding its
+            // pipeline once.
+            draws.sort_by_key(|(_, object)| object.shading_model as usize);
+
+            let mut graph = RenderGraph::default();
+            graph.add_pass(
+                "scene",
+                &[],
+                &[
+                    (SCENE_COLOR, GraphTextureDesc::color(target_format)),
+                    (SCENE_NORMALS, GraphTextureDesc::color(NORMAL_FORMAT)),
+                    (
+                        SCENE_DEPTH,
+                        GraphTextureDesc::depth(wgpu::TextureFormat::Depth32Float),
+                    ),
+                ],
+                |encoder, ctx| {
+                    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: None,
+                        color_attachments: &[
+                            Some(wgpu::RenderPassColorAttachment {
+                                view: ctx.view(SCENE_COLOR),
+                                resolve_target: None,
+                                ops: wgpu::Operations {
+                                    load: wgpu::LoadOp::Clear(clear_color),
+                                    store: wgpu::StoreOp::Store,
+                                },
+                            }),
+                            Some(wgpu::RenderPassColorAttachment {
+                                view: ctx.view(SCENE_NORMALS),
+                                resolve_target: None,
+                                ops: wgpu::Operations {
+                                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                                    store: wgpu::StoreOp::Store,
+                                },
+                            }),
+                        ],
+                        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                            view: ctx.view(SCENE_DEPTH),
+                            depth_ops: Some(wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(1.0),
+                                store: wgpu::StoreOp::Store,
+                            }),
+                            stencil_ops: None,
+                        }),
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+
+                    let debug_pipeline = match view_mode {
+                        config::ViewMode::Shaded => None,
+                        config::ViewMode::Overdraw => Some(&resources.debug_view.overdraw_pipeline),
+                        _ => Some(&resources.debug_view.pipeline),
+                    };
+                    if let Some(pipeline) = debug_pipeline {
+                        pass.set_pipeline(pipeline);
+                        pass.set_bind_group(2, &resources.debug_view.params_bind_group, &[]);
+                    }
+                    pass.set_bind_group(1, &resources.light_bind_group, &[]);
+                    let mut bound = None;
+                    for (offset, object) in &draws {
+                        if debug_pipeline.is_none() {
+                            let Some(pipeline) = resources.pipelines.get(&object.shading_model)
+                            else {
+                                continue;
+                            };
+                            if bound != Some(object.shading_model) {
+                                pass.set_pipeline(pipeline);
+                                bound = Some(object.shading_model);
+                            }
+                            pass.set_bind_group(2, &object.params_bind_group, &[]);
+                        }
+                        pass.set_bind_group(0, &resources.camera_bind_group, &[*offset]);
+                        pass.set_vertex_buffer(0, object.vertex_buffer.slice(..));
+                        for part in &object.parts {
+                            pass.set_bind_group(3, &part.texture_bind_group, &[]);
+                            pass.draw(part.vertices.clone(), 0..1);
+                        }
+                    }
+
+                    if self.wireframe.is_some() {
+                        pass.set_pipeline(&resources.wireframe.pipeline);
+                        pass.set_bind_group(1, &resources.wireframe.params_bind_group, &[]);
+                        for (offset, object) in &draws {
+                            pass.set_bind_group(0, &resources.camera_bind_group, &[*offset]);
+                            pass.set_vertex_buffer(0, object.vertex_buffer.slice(..));
+                            pass.draw(0..object.vertex_count, 0..1);
+                        }
+                    }
+                },
+            );
+
+            let mut input = SCENE_COLOR.to_string();
+            for (i, entry) in post_effects.into_iter().enumerate() {
+                let output = post_output_name(i);
+                let params_offset = i as u64 * POST_PARAMS_SIZE;
+                let (name, params) = {
+                    let mut effect = entry.effect.lock().unwrap();
+                    effect.begin_frame(self.frame.frame, i);
+                    (effect.as_enum().to_string(), effect.to_params().to_vec())
+                };
+                queue.write_buffer(params_buffer, params_offset, &params);
+
+                let history = post_history_name(i);
+                if self
+                    .inspect
+                    .as_ref()
+                    .is_some_and(|request| request.resource == history)
+                {
+                    // Added ahead of the effect, so it copies last frame's history.
+                    graph.add_pass(
+                        "history",
+                        &[],
+                        &[(&history, GraphTextureDesc::color(target_format))],
+                        {
+                            let history = history.clone();
+                            move |encoder, ctx| {
+                                let effect = entry.effect.lock().unwrap();
+                                copy_history(encoder, effect.history(), ctx.texture(&history));
+                            }
+                        },
+                    );
+                }
+
+                graph.add_pass(
+                    name,
+                    &[&input, SCENE_DEPTH, SCENE_NORMALS],
+                    &[(&output, GraphTextureDesc::color(target_format))],
+                    {
+                        let (input, output) = (input.clone(), output.clone());
+                        move |encoder, ctx| {
+                            let mut post_guard = entry.effect.lock().unwrap();
+                            let target = ctx.texture(&output).clone();
+                            let mix = entry.mix.clamp(0.0, 1.0) as f64;
+                            let load = if mix < 1.0 {
+                                // Partially mixed effects blend over a copy of their input.
+                                encoder.copy_texture_to_texture(
+                                    ctx.texture(&input).texture.as_image_copy(),
+                                    target.texture.as_image_copy(),
+                                    target.texture.size(),
+                                );
+                                wgpu::LoadOp::Load
+                            } else {
+                                wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT)
+                            };
+                            let scene_bind_group = ctx.cached_bind_group(
+                                "post_scene",
+                                &[SCENE_DEPTH, SCENE_NORMALS],
+                                |device, views| {
+                                    create_post_scene_bind_group(
+                                        device,
+                                        views[0],
+                                        views[1],
+                                        camera_buffer,
+                                        frame_buffer,
+                                    )
+                                },
+                            );
+
+                            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                                label: None,
+                                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                                    view: &target.view,
+                                    resolve_target: None,
+                                    ops: wgpu::Operations {
+                                        load,
+                                        store: wgpu::StoreOp::Store,
+                                    },
+                                })],
+                                depth_stencil_attachment: None,
+                                timestamp_writes: None,
+                                occlusion_query_set: None,
+                            });
+                            pass.set_pipeline(post_guard.get_pipeline(ctx.device, target_format));
+                            pass.set_blend_constant(wgpu::Color {
+                                r: mix,
+                                g: mix,
+                                b: mix,
+                                a: mix,
+                            });
+                            pass.set_bind_group(0, ctx.sampled(&input), &[]);
+                            pass.set_bind_group(1, &scene_bind_group, &[]);
+                            pass.set_bind_group(2, params_bind_group, &[params_offset as u32]);
+                            if let Some(effect_bind_group) =
+                                post_guard.get_bind_group(ctx.device, ctx.queue)
+                            {
+                                pass.set_bind_group(3, effect_bind_group, &[]);
+                            }
+                            pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                            pass.draw(0..6, 0..1);
+                            drop(pass);
+                            post_guard.finish(ctx.device, encoder, &target.texture);
+                        }
                     },
-                })],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &post.depth_texture_view,
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store,
-                    }),
-                    stencil_ops: None,
-                }),
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
+                );
+                input = output;
+            }
+            graph.set_output(&input);
+
+            if let Some(request) = &self.inspect
+                && graph.writes(&request.resource)
+                && let Some(inspector) = &resources.inspector
+            {
+                inspector.write_params(queue, request, self.view_projection.inv_proj);
+                graph.add_pass("inspect", &[&request.resource], &[], |encoder, ctx| {
+                    inspector.draw(encoder, ctx, &request.resource, vertex_buffer)
+                });
+            }
 
-            pass.set_pipeline(&resources.pipeline);
-            pass.set_bind_group(0, &resources.camera_bind_group, &[]);
-            pass.set_bind_group(1, &resources.light_bind_group, &[]);
-            pass.set_bind_group(2, &resources.params_bind_group, &[]);
-            pass.set_bind_group(3, &resources.model_texture_bind_group, &[]);
-            pass.set_vertex_buffer(0, resources.vertex_buffer.slice(..));
-            pass.draw(0..resources.vertex_count, 0..1);
-            drop(pass);
-
-            for post_effect in self.post_effects.iter() {
-                let mut post_guard = post_effect.lock().unwrap();
-                let post_effect_pipeline = post_guard.get_pipeline(device, post.target_format);
-                post.swap_buffers();
-                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: None,
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: post.get_texture_out_view(),
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
-                            store: wgpu::StoreOp::Store,
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                    timestamp_writes: None,
-                    occlusion_query_set: None,
+            if let Some(pixel) = self.pick
+                && resources.picker.is_idle()
+            {
+                let inv_view_proj = glam::Mat4::from_cols_array_2d(&self.view_projection.inv_view)
+                    * glam::Mat4::from_cols_array_2d(&self.view_projection.inv_proj);
+                let picker = &mut resources.picker;
+                graph.add_pass("pick", &[SCENE_DEPTH], &[], move |encoder, ctx| {
+                    picker.copy(
+                        encoder,
+                        &ctx.texture(SCENE_DEPTH).texture,
+                        pixel,
+                        inv_view_proj,
+                    )
                 });
-                pass.set_pipeline(post_effect_pipeline);
-                pass.set_bind_group(0, post.get_bind_group_in(), &[]);
-                pass.set_vertex_buffer(0, post.vertex_buffer.slice(..));
-                pass.draw(0..6, 0..1);
             }
+
+            let mut encoder = device.create_command_encoder(&Default::default());
+            post.output = graph.execute(device, queue, &mut encoder, &mut post.pool);
             queue.submit([encoder.finish()]);
+            resources.picker.map();
         }
         Vec::new()
     }
@@ -599,53 +1348,130 @@ impl egui_wgpu::CallbackTrait for ObjectRenderCallback {
     }
 }
 
+/// What the renderer keeps of one scene object.
+struct SceneObjectResources {
+    shading_model: config::ShadingModelEnum,
+    params_layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+    params_bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+    vertex_count: u32,
+    parts: Vec<SceneObjectPart>,
+}
+
+/// A range of an object's vertices sharing one texture.
+struct SceneObjectPart {
+    vertices: std::ops::Range<u32>,
+    texture_bind_group: wgpu::BindGroup,
+}
+
 pub struct ObjectRenderResources {
-    pipeline: wgpu::RenderPipeline,
+    /// Scene pipeline of each shading model in use, built when first drawn.
+    pipelines: HashMap<config::ShadingModelEnum, wgpu::RenderPipeline>,
+    settings: PipelineSettings,
+    target_format: wgpu::TextureFormat,
     camera_buffer: wgpu::Buffer,
+    model_buffer: wgpu::Buffer,
+    model_capacity: u64,
     camera_bind_group: wgpu::BindGroup,
     light_buffer: wgpu::Buffer,
     light_bind_group: wgpu::BindGroup,
-    params_buffer: wgpu::Buffer,
-    params_bind_group: wgpu::BindGroup,
-    vertex_buffer: wgpu::Buffer,
-    vertex_count: u32,
+    objects: HashMap<u64, SceneObjectResources>,
     post_process_resources: Option<PostProcessResources>,
-    _model_texture_view: wgpu::TextureView,
-    _model_texture_sampler: wgpu::Sampler,
-    model_texture_bind_group: wgpu::BindGroup,
+    /// Bound in place of the texture of objects whose material has none.
+    proxy_texture_view: wgpu::TextureView,
+    proxy_texture_sampler: wgpu::Sampler,
+    prev_view_proj: Option<[[f32; 4]; 4]>,
+    inspector: Option<inspect::TextureInspector>,
+    picker: pick::DepthPicker,
+    debug_view: DebugViewResources,
+    wireframe: WireframeResources,
+    camera_bind_group_layout: wgpu::BindGroupLayout,
+    light_bind_group_layout: wgpu::BindGroupLayout,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
 }
 
 impl ObjectRenderResources {
-    pub fn set_vertex_buffer(&mut self, vertex_buffer: wgpu::Buffer, vertex_count: u32) {
-        self.vertex_buffer = vertex_buffer;
-        self.vertex_count = vertex_count;
-    }
-
     pub fn set_post_process_resources(&mut self, post_process_resources: PostProcessResources) {
         self.post_process_resources = Some(post_process_resources);
     }
 
     fn prepare(
-        &self,
-        _device: &wgpu::Device,
+        &mut self,
+        device: &wgpu::Device,
         queue: &wgpu::Queue,
         view_projection: &CameraUniform,
+        objects: &[ObjectDraw],
         light: &LightUniform,
-        params: Arc<Mutex<dyn ShadingModel + Send>>,
     ) {
+        let mut view_projection = *view_projection;
+        if let Some(prev_view_proj) = self.prev_view_proj {
+            view_projection.prev_view_proj = prev_view_proj;
+        }
+        self.prev_view_proj = Some(view_projection.view_proj);
         queue.write_buffer(
             &self.camera_buffer,
             0,
-            bytemuck::cast_slice(&[*view_projection]),
+            bytemuck::cast_slice(&[view_projection]),
         );
         queue.write_buffer(&self.light_buffer, 0, bytemuck::cast_slice(&[*light]));
-        queue.write_buffer(&self.params_buffer, 0, params.lock().unwrap().to_params());
+
+        self.reserve_models(device, objects.len() as u64);
+        for (index, draw) in objects.iter().enumerate() {
+            queue.write_buffer(
+                &self.model_buffer,
+                index as u64 * MODEL_UNIFORM_SIZE,
+                bytemuck::bytes_of(&draw.model),
+            );
+            let Some(object) = self.objects.get(&draw.id) else {
+                continue;
+            };
+            let shading_model = draw.shading_model.lock().unwrap();
+            // A model switched since the object's params were made doesn't fit them.
+            if shading_model.as_enum() != object.shading_model {
+                continue;
+            }
+            queue.write_buffer(&object.params_buffer, 0, shading_model.to_params());
+            if !self.pipelines.contains_key(&object.shading_model) {
+                let pipeline = create_scene_pipeline(
+                    device,
+                    self.target_format,
+                    shading_model.get_source(),
+                    &[
+                        &self.camera_bind_group_layout,
+                        &self.light_bind_group_layout,
+                        &object.params_layout,
+                        &self.texture_bind_group_layout,
+                    ],
+                    &self.settings,
+                );
+                self.pipelines.insert(object.shading_model, pipeline);
+            }
+        }
+    }
+
+    /// Makes sure the model buffer has a slot for each of `count` objects.
+    fn reserve_models(&mut self, device: &wgpu::Device, count: u64) {
+        if count <= self.model_capacity {
+            return;
+        }
+        let (model_buffer, camera_bind_group) = create_model_uniforms(
+            device,
+            &self.camera_bind_group_layout,
+            &self.camera_buffer,
+            count,
+        );
+        self.model_buffer = model_buffer;
+        self.camera_bind_group = camera_bind_group;
+        self.model_capacity = count;
     }
 
     fn paint(&self, render_pass: &mut wgpu::RenderPass<'_>) {
-        if let Some(pp) = &self.post_process_resources {
+        if let Some(pp) = &self.post_process_resources
+            && let Some(output) = &pp.output
+        {
             render_pass.set_pipeline(&pp.pipeline);
-            render_pass.set_bind_group(0, pp.get_final_bind_group(), &[]);
+            render_pass.set_bind_group(0, output.sampled(), &[]);
             render_pass.set_vertex_buffer(0, pp.vertex_buffer.slice(..));
             render_pass.draw(0..6, 0..1);
         }
@@ -657,35 +1483,66 @@ impl ObjectRenderResources {
 pub struct CameraUniform {
     pub proj: [[f32; 4]; 4],
     pub view: [[f32; 4]; 4],
+    pub inv_proj: [[f32; 4]; 4],
+    pub inv_view: [[f32; 4]; 4],
+    /// View-projection without the sub-pixel jitter.
+    pub view_proj: [[f32; 4]; 4],
+    /// Unjittered view-projection of the previous frame, filled in by the renderer.
+    pub prev_view_proj: [[f32; 4]; 4],
+    pub jitter: [f32; 4],
 }
 
 impl CameraUniform {
     pub fn from_camera(camera: &crate::camera::WorldCamera) -> Self {
+        let proj = camera.build_projection();
+        let view = camera.build_view();
+        let unjitter = glam::Mat4::from_translation(-camera.jitter().extend(0.0));
+        let view_proj = (unjitter * proj * view).to_cols_array_2d();
         Self {
-            proj: camera.build_projection().to_cols_array_2d(),
-            view: camera.build_view().to_cols_array_2d(),
+            proj: proj.to_cols_array_2d(),
+            view: view.to_cols_array_2d(),
+            inv_proj: proj.inverse().to_cols_array_2d(),
+            inv_view: view.inverse().to_cols_array_2d(),
+            view_proj,
+            prev_view_proj: view_proj,
+            jitter: [camera.jitter().x, camera.jitter().y, 0.0, 0.0],
         }
     }
 
-    fn create_uniform(
-        device: &wgpu::Device,
-    ) -> (wgpu::BindGroupLayout, wgpu::BindGroup, wgpu::Buffer) {
+    /// The camera at binding 0 and the drawn object's [`ModelUniform`] at binding 1,
+    /// picked by dynamic offset. See [`create_model_uniforms`] for the bind group.
+    fn create_uniform(device: &wgpu::Device) -> (wgpu::BindGroupLayout, wgpu::Buffer) {
         let camera_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: None,
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: Some(
-                            wgpu::BufferSize::new(std::mem::size_of::<CameraUniform>() as u64)
-                                .unwrap(),
-                        ),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: Some(
+                                wgpu::BufferSize::new(std::mem::size_of::<CameraUniform>() as u64)
+                                    .unwrap(),
+                            ),
+                        },
+                        count: None,
                     },
-                    count: None,
-                }],
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: true,
+                            min_binding_size: Some(
+                                wgpu::BufferSize::new(std::mem::size_of::<ModelUniform>() as u64)
+                                    .unwrap(),
+                            ),
+                        },
+                        count: None,
+                    },
+                ],
             });
 
         let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
@@ -695,16 +1552,89 @@ impl CameraUniform {
             mapped_at_creation: false,
         });
 
-        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: None,
-            layout: &camera_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
+        (camera_bind_group_layout, camera_buffer)
+    }
+}
+
+/// A model buffer with `count` slots and the camera bind group reading it.
+fn create_model_uniforms(
+    device: &wgpu::Device,
+    camera_layout: &wgpu::BindGroupLayout,
+    camera_buffer: &wgpu::Buffer,
+    count: u64,
+) -> (wgpu::Buffer, wgpu::BindGroup) {
+    let model_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("model"),
+        size: MODEL_UNIFORM_SIZE * count.max(1),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: None,
+        layout: camera_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
                 binding: 0,
                 resource: camera_buffer.as_entire_binding(),
-            }],
-        });
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &model_buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(std::mem::size_of::<ModelUniform>() as u64),
+                }),
+            },
+        ],
+    });
+
+    (model_buffer, camera_bind_group)
+}
+
+/// Model-to-world transform of the drawn object, at group 0, binding 1 of the scene
+/// pipelines. Each object has its own slot in the model buffer.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ModelUniform {
+    model: [[f32; 4]; 4],
+    /// Inverse transpose of the model matrix, so normals stay perpendicular under
+    /// non-uniform scale. A `mat3x3` in WGSL, with its columns padded to 16 bytes.
+    normal: [[f32; 4]; 3],
+}
+
+impl ModelUniform {
+    pub fn new(model: glam::Mat4) -> Self {
+        let normal = glam::Mat3::from_mat4(model).inverse().transpose();
+        Self {
+            model: model.to_cols_array_2d(),
+            normal: [
+                normal.x_axis.extend(0.0).to_array(),
+                normal.y_axis.extend(0.0).to_array(),
+                normal.z_axis.extend(0.0).to_array(),
+            ],
+        }
+    }
+}
+
+/// Per-frame timing available to post effects at group 1, binding 3.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct FrameUniform {
+    pub time: f32,
+    pub delta_time: f32,
+    pub frame: u32,
+    _padding: u32,
+}
 
-        (camera_bind_group_layout, camera_bind_group, camera_buffer)
+impl FrameUniform {
+    pub fn new(time: f32, delta_time: f32, frame: u32) -> Self {
+        Self {
+            time,
+            delta_time,
+            frame,
+            _padding: 0,
+        }
     }
 }
 
@@ -745,14 +1675,31 @@ pub struct Vertex {
     position: [f32; 3],
     normal: [f32; 3],
     texcoord: [f32; 2],
+    /// Direction of increasing u, with the sign of the bitangent in w.
+    tangent: [f32; 4],
+    /// Linear RGBA, white for meshes without vertex colours.
+    color: [f32; 4],
+    /// Which of the triangle's edges the wireframe draws, bit `i` for the edge facing
+    /// corner `i`. Diagonals cut through a polygon are left out.
+    edges: u32,
 }
 
 impl Vertex {
-    pub fn new(position: [f32; 3], normal: [f32; 3], texcoord: [f32; 2]) -> Vertex {
+    pub fn new(
+        position: [f32; 3],
+        normal: [f32; 3],
+        texcoord: [f32; 2],
+        tangent: [f32; 4],
+        color: [f32; 4],
+        edges: u32,
+    ) -> Vertex {
         Vertex {
             position,
             normal,
             texcoord,
+            tangent,
+            color,
+            edges,
         }
     }
     pub fn desc() -> wgpu::VertexBufferLayout<'static> {
@@ -775,6 +1722,28 @@ impl Vertex {
                     shader_location: 2,
                     format: wgpu::VertexFormat::Float32x2,
                 },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 3]>() * 2 + std::mem::size_of::<[f32; 2]>())
+                        as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 3]>() * 2
+                        + std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<[f32; 4]>())
+                        as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 3]>() * 2
+                        + std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<[f32; 4]>() * 2)
+                        as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Uint32,
+                },
             ],
         }
     }
@@ -0,0 +1,468 @@
+//! A small per-frame render graph.
+//!
+//! Passes name the textures they read and write. The graph runs them in dependency
+//! order and hands out viewport-sized textures from a [`TexturePool`] that survives
+//! across frames, recycling a texture as soon as its last reader has run.
+
+use std::collections::{HashMap, HashSet};
+
+use eframe::egui_wgpu::wgpu;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GraphTextureDesc {
+    pub format: wgpu::TextureFormat,
+    pub usage: wgpu::TextureUsages,
+    /// Divisor applied to the viewport size, 1 for full resolution.
+    pub downscale: u32,
+}
+
+impl GraphTextureDesc {
+    /// A colour target that can also be sampled and copied.
+    pub const fn color(format: wgpu::TextureFormat) -> Self {
+        Self {
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                .union(wgpu::TextureUsages::TEXTURE_BINDING)
+                .union(wgpu::TextureUsages::COPY_SRC)
+                .union(wgpu::TextureUsages::COPY_DST),
+            downscale: 1,
+        }
+    }
+
+    /// A depth target that can also be sampled and read back.
+    pub const fn depth(format: wgpu::TextureFormat) -> Self {
+        Self {
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                .union(wgpu::TextureUsages::TEXTURE_BINDING)
+                .union(wgpu::TextureUsages::COPY_SRC),
+            downscale: 1,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GraphTexture {
+    id: u64,
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    /// The texture with the shared filtering sampler, laid out as group 0 of a post
+    /// pipeline. `None` for formats that can't be filtered.
+    sampled: Option<wgpu::BindGroup>,
+}
+
+impl GraphTexture {
+    pub fn sampled(&self) -> Option<&wgpu::BindGroup> {
+        self.sampled.as_ref()
+    }
+}
+
+type BindGroupKey = (&'static str, Vec<u64>);
+
+/// Textures owned by the graph, kept between frames and dropped on resize.
+pub struct TexturePool {
+    size: (u32, u32),
+    input_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    textures: Vec<(GraphTextureDesc, GraphTexture)>,
+    bind_groups: HashMap<BindGroupKey, wgpu::BindGroup>,
+    next_id: u64,
+}
+
+impl TexturePool {
+    pub fn new(device: &wgpu::Device, size: (u32, u32)) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self {
+            size,
+            input_layout: super::post_input_bind_group_layout(device),
+            sampler,
+            textures: Vec::new(),
+            bind_groups: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn resize(&mut self, size: (u32, u32)) {
+        if size != self.size {
+            self.size = size;
+            self.textures.clear();
+            self.bind_groups.clear();
+        }
+    }
+
+    /// Returns a pooled texture matching `desc` that isn't in `busy`, creating one if needed.
+    fn acquire(
+        &mut self,
+        device: &wgpu::Device,
+        desc: GraphTextureDesc,
+        busy: &HashSet<u64>,
+    ) -> GraphTexture {
+        if let Some((_, texture)) = self
+            .textures
+            .iter()
+            .find(|(d, texture)| *d == desc && !busy.contains(&texture.id))
+        {
+            return texture.clone();
+        }
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("graph_texture"),
+            size: wgpu::Extent3d {
+                width: (self.size.0 / desc.downscale).max(1),
+                height: (self.size.1 / desc.downscale).max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: desc.format,
+            usage: desc.usage,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&Default::default());
+
+        let filterable = desc.format.sample_type(None, None)
+            == Some(wgpu::TextureSampleType::Float { filterable: true });
+        let sampled = (filterable && desc.usage.contains(wgpu::TextureUsages::TEXTURE_BINDING))
+            .then(|| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &self.input_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(&view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(&self.sampler),
+                        },
+                    ],
+                    label: None,
+                })
+            });
+
+        let texture = GraphTexture {
+            id: self.next_id,
+            texture,
+            view,
+            sampled,
+        };
+        self.next_id += 1;
+        self.textures.push((desc, texture.clone()));
+        texture
+    }
+}
+
+/// What a pass gets to see of the graph while it records its commands.
+pub struct GraphContext<'g> {
+    pub device: &'g wgpu::Device,
+    pub queue: &'g wgpu::Queue,
+    textures: &'g HashMap<String, GraphTexture>,
+    bind_groups: &'g mut HashMap<BindGroupKey, wgpu::BindGroup>,
+}
+
+impl GraphContext<'_> {
+    /// A texture the pass declared as an input or output.
+    pub fn texture(&self, name: &str) -> &GraphTexture {
+        self.textures
+            .get(name)
+            .unwrap_or_else(|| panic!("render graph: `{name}` isn't available to this pass"))
+    }
+
+    pub fn view(&self, name: &str) -> &wgpu::TextureView {
+        &self.texture(name).view
+    }
+
+    /// Group 0 of a post pipeline reading `name`.
+    pub fn sampled(&self, name: &str) -> &wgpu::BindGroup {
+        self.texture(name)
+            .sampled()
+            .unwrap_or_else(|| panic!("render graph: `{name}` can't be sampled with filtering"))
+    }
+
+    /// A bind group over the named textures, reused across frames until any of them
+    /// is reallocated. Anything else it binds must outlive the pool.
+    pub fn cached_bind_group(
+        &mut self,
+        label: &'static str,
+        names: &[&str],
+        create: impl FnOnce(&wgpu::Device, &[&wgpu::TextureView]) -> wgpu::BindGroup,
+    ) -> wgpu::BindGroup {
+        let key = (label, names.iter().map(|n| self.texture(n).id).collect());
+        if let Some(bind_group) = self.bind_groups.get(&key) {
+            return bind_group.clone();
+        }
+        let views: Vec<&wgpu::TextureView> = names.iter().map(|n| self.view(n)).collect();
+        let bind_group = create(self.device, &views);
+        self.bind_groups.insert(key, bind_group.clone());
+        bind_group
+    }
+}
+
+type PassFn<'a> = Box<dyn FnOnce(&mut wgpu::CommandEncoder, &mut GraphContext) + 'a>;
+
+struct GraphPass<'a> {
+    name: String,
+    inputs: Vec<String>,
+    outputs: Vec<(String, GraphTextureDesc)>,
+    execute: PassFn<'a>,
+}
+
+/// Built fresh every frame, then consumed by [`RenderGraph::execute`].
+#[derive(Default)]
+pub struct RenderGraph<'a> {
+    passes: Vec<GraphPass<'a>>,
+    output: Option<String>,
+}
+
+impl<'a> RenderGraph<'a> {
+    /// Adds a pass reading `inputs` and writing fresh textures for `outputs`. A pass
+    /// can't write a texture another pass produced; write a new name instead.
+    pub fn add_pass(
+        &mut self,
+        name: impl Into<String>,
+        inputs: &[&str],
+        outputs: &[(&str, GraphTextureDesc)],
+        execute: impl FnOnce(&mut wgpu::CommandEncoder, &mut GraphContext) + 'a,
+    ) {
+        self.passes.push(GraphPass {
+            name: name.into(),
+            inputs: inputs.iter().map(|s| s.to_string()).collect(),
+            outputs: outputs.iter().map(|(s, d)| (s.to_string(), *d)).collect(),
+            execute: Box::new(execute),
+        });
+    }
+
+    /// Whether some pass writes `name`.
+    pub fn writes(&self, name: &str) -> bool {
+        self.passes
+            .iter()
+            .any(|pass| pass.outputs.iter().any(|(output, _)| output == name))
+    }
+
+    /// Keeps `name` alive past the graph; [`RenderGraph::execute`] returns it.
+    pub fn set_output(&mut self, name: &str) {
+        self.output = Some(name.to_string());
+    }
+
+    /// Pass indices in execution order: every pass after the producers of its inputs,
+    /// otherwise in the order they were added.
+    fn order(&self) -> Vec<usize> {
+        let mut producers = HashMap::new();
+        for (index, pass) in self.passes.iter().enumerate() {
+            for (output, _) in &pass.outputs {
+                if let Some(other) = producers.insert(output.as_str(), index) {
+                    panic!(
+                        "render graph: `{output}` is written by both `{}` and `{}`",
+                        self.passes[other].name, pass.name
+                    );
+                }
+            }
+        }
+
+        let mut scheduled = vec![false; self.passes.len()];
+        let mut order = Vec::with_capacity(self.passes.len());
+        while order.len() < self.passes.len() {
+            let ready = (0..self.passes.len()).find(|&index| {
+                !scheduled[index]
+                    && self.passes[index].inputs.iter().all(|input| {
+                        let producer = producers.get(input.as_str()).unwrap_or_else(|| {
+                            panic!(
+                                "render graph: `{}` reads `{input}` which no pass writes",
+                                self.passes[index].name
+                            )
+                        });
+                        scheduled[*producer]
+                    })
+            });
+            let Some(index) = ready else {
+                panic!("render graph: passes form a cycle");
+            };
+            scheduled[index] = true;
+            order.push(index);
+        }
+        order
+    }
+
+    pub fn execute(
+        self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        pool: &mut TexturePool,
+    ) -> Option<GraphTexture> {
+        let order = self.order();
+
+        // The step after which each texture can go back to the pool.
+        let mut last_use: HashMap<String, usize> = HashMap::new();
+        for (step, &index) in order.iter().enumerate() {
+            let pass = &self.passes[index];
+            for name in pass
+                .inputs
+                .iter()
+                .chain(pass.outputs.iter().map(|(n, _)| n))
+            {
+                last_use.insert(name.clone(), step);
+            }
+        }
+        if let Some(output) = &self.output {
+            last_use.insert(output.clone(), usize::MAX);
+        }
+
+        let mut passes: Vec<Option<GraphPass>> = self.passes.into_iter().map(Some).collect();
+        let mut live: HashMap<String, GraphTexture> = HashMap::new();
+        for (step, index) in order.into_iter().enumerate() {
+            let pass = passes[index].take().unwrap();
+            for (name, desc) in &pass.outputs {
+                let busy = live.values().map(|texture| texture.id).collect();
+                let texture = pool.acquire(device, *desc, &busy);
+                live.insert(name.clone(), texture);
+            }
+
+            let mut context = GraphContext {
+                device,
+                queue,
+                textures: &live,
+                bind_groups: &mut pool.bind_groups,
+            };
+            (pass.execute)(encoder, &mut context);
+            live.retain(|name, _| last_use[name] > step);
+        }
+
+        self.output.and_then(|output| live.remove(&output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLOR: GraphTextureDesc = GraphTextureDesc::color(wgpu::TextureFormat::Rgba8Unorm);
+
+    fn graph(passes: &[(&str, &[&str], &[&str])]) -> RenderGraph<'static> {
+        let mut graph = RenderGraph::default();
+        for &(name, inputs, outputs) in passes {
+            let outputs: Vec<_> = outputs.iter().map(|&output| (output, COLOR)).collect();
+            graph.add_pass(name, inputs, &outputs, |_, _| {});
+        }
+        graph
+    }
+
+    #[test]
+    fn runs_readers_after_writers() {
+        let graph = graph(&[
+            ("post", &["color"], &["out"]),
+            ("overlay", &["out", "depth"], &["final"]),
+            ("scene", &[], &["color", "depth"]),
+            ("ui", &[], &["ui"]),
+        ]);
+        assert_eq!(graph.order(), [2, 0, 1, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`color` is written by both `scene` and `post`")]
+    fn rejects_two_writers() {
+        graph(&[("scene", &[], &["color"]), ("post", &[], &["color"])]).order();
+    }
+
+    #[test]
+    #[should_panic(expected = "cycle")]
+    fn rejects_cycles() {
+        graph(&[
+            ("scene", &[], &["color"]),
+            ("a", &["color", "b"], &["a"]),
+            ("b", &["a"], &["b"]),
+        ])
+        .order();
+    }
+
+    #[test]
+    #[should_panic(expected = "`post` reads `color` which no pass writes")]
+    fn rejects_missing_inputs() {
+        graph(&[("post", &["color"], &["out"])]).order();
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+        loop {
+            if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    /// A device on whatever adapter there is, or `None` on machines without one.
+    fn device() -> Option<(wgpu::Device, wgpu::Queue)> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = block_on(instance.request_adapter(&Default::default())).ok()?;
+        block_on(adapter.request_device(&Default::default())).ok()
+    }
+
+    /// Runs a chain of three passes and returns the size of its output.
+    fn run_chain(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        pool: &mut TexturePool,
+    ) -> wgpu::Extent3d {
+        let mut graph = graph(&[
+            ("scene", &[], &["color"]),
+            ("blur", &["color"], &["blurred"]),
+            ("grade", &["blurred"], &["graded"]),
+        ]);
+        graph.set_output("graded");
+        let mut encoder = device.create_command_encoder(&Default::default());
+        let output = graph.execute(device, queue, &mut encoder, pool).unwrap();
+        output.texture.size()
+    }
+
+    #[test]
+    fn recycles_textures() {
+        let Some((device, queue)) = device() else {
+            eprintln!("no adapter, skipping");
+            return;
+        };
+        let mut pool = TexturePool::new(&device, (32, 16));
+        run_chain(&device, &queue, &mut pool);
+        // `color` is free again once `blur` ran, so `grade` writes into it.
+        assert_eq!(pool.textures.len(), 2);
+        run_chain(&device, &queue, &mut pool);
+        assert_eq!(pool.textures.len(), 2);
+    }
+
+    #[test]
+    fn reallocates_on_resize() {
+        let Some((device, queue)) = device() else {
+            eprintln!("no adapter, skipping");
+            return;
+        };
+        let mut pool = TexturePool::new(&device, (32, 16));
+        assert_eq!(run_chain(&device, &queue, &mut pool).width, 32);
+        let ids: Vec<u64> = pool
+            .textures
+            .iter()
+            .map(|(_, texture)| texture.id)
+            .collect();
+
+        pool.resize((32, 16));
+        assert_eq!(pool.textures.len(), 2);
+        pool.resize((64, 48));
+        assert!(pool.textures.is_empty());
+        let size = run_chain(&device, &queue, &mut pool);
+        assert_eq!((size.width, size.height), (64, 48));
+        assert!(
+            pool.textures
+                .iter()
+                .all(|(_, texture)| !ids.contains(&texture.id))
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! Copies a render graph texture into a texture registered with egui, for the
+//! texture viewer panel.
+
+use eframe::egui_wgpu::{self, wgpu};
+
+use super::{ObjectRenderResources, PostVertex, graph::GraphContext};
+
+/// Format of the texture egui displays. Matches egui's own textures.
+const INSPECT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum InspectChannel {
+    Rgb,
+    Red,
+    Green,
+    Blue,
+    Alpha,
+}
+
+impl InspectChannel {
+    pub const ALL: [InspectChannel; 5] = [
+        InspectChannel::Rgb,
+        InspectChannel::Red,
+        InspectChannel::Green,
+        InspectChannel::Blue,
+        InspectChannel::Alpha,
+    ];
+}
+
+impl std::fmt::Display for InspectChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InspectChannel::Rgb => write!(f, "RGB"),
+            InspectChannel::Red => write!(f, "R"),
+            InspectChannel::Green => write!(f, "G"),
+            InspectChannel::Blue => write!(f, "B"),
+            InspectChannel::Alpha => write!(f, "A"),
+        }
+    }
+}
+
+/// Which graph texture to show this frame, and how.
+#[derive(Clone)]
+pub struct InspectRequest {
+    pub resource: String,
+    pub channel: InspectChannel,
+    pub linearize_depth: bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct InspectParams {
+    inv_proj: [[f32; 4]; 4],
+    channel: u32,
+    linearize_depth: u32,
+    _padding: [u32; 2],
+}
+
+pub struct TextureInspector {
+    color_pipeline: wgpu::RenderPipeline,
+    depth_pipeline: wgpu::RenderPipeline,
+    depth_layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+    params_bind_group: wgpu::BindGroup,
+    target_view: wgpu::TextureView,
+    texture_id: egui::TextureId,
+}
+
+impl TextureInspector {
+    fn new(
+        device: &wgpu::Device,
+        target_view: wgpu::TextureView,
+        texture_id: egui::TextureId,
+    ) -> Self {
+        let depth_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Depth,
+                },
+                count: None,
+            }],
+            label: Some("inspect_depth"),
+        });
+
+        let params_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("inspect_params"),
+        });
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("inspect_params"),
+            size: std::mem::size_of::<InspectParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let params_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &params_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buffer.as_entire_binding(),
+            }],
+            label: Some("inspect_params"),
+        });
+
+        let color_pipeline = Self::create_pipeline(
+            device,
+            include_str!("inspect.wgsl"),
+            &super::post_input_bind_group_layout(device),
+            &params_layout,
+        );
+        let depth_pipeline = Self::create_pipeline(
+            device,
+            include_str!("inspect_depth.wgsl"),
+            &depth_layout,
+            &params_layout,
+        );
+
+        Self {
+            color_pipeline,
+            depth_pipeline,
+            depth_layout,
+            params_buffer,
+            params_bind_group,
+            target_view,
+            texture_id,
+        }
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        src: &str,
+        input_layout: &wgpu::BindGroupLayout,
+        params_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("inspect"),
+            source: wgpu::ShaderSource::Wgsl(src.into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[input_layout, params_layout],
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("inspect"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[PostVertex::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(INSPECT_FORMAT.into())],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    pub fn write_params(
+        &self,
+        queue: &wgpu::Queue,
+        request: &InspectRequest,
+        inv_proj: [[f32; 4]; 4],
+    ) {
+        let params = InspectParams {
+            inv_proj,
+            channel: request.channel as u32,
+            linearize_depth: request.linearize_depth as u32,
+            _padding: [0; 2],
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+    }
+
+    /// Records the draw of `resource` into the egui texture. Runs as a graph pass
+    /// reading `resource`.
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        ctx: &mut GraphContext,
+        resource: &str,
+        vertex_buffer: &wgpu::Buffer,
+    ) {
+        let is_depth = ctx
+            .texture(resource)
+            .texture
+            .format()
+            .is_depth_stencil_format();
+        let (pipeline, input) = if is_depth {
+            let input = ctx.cached_bind_group("inspect_depth", &[resource], |device, views| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &self.depth_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(views[0]),
+                    }],
+                    label: Some("inspect_depth"),
+                })
+            });
+            (&self.depth_pipeline, input)
+        } else {
+            (&self.color_pipeline, ctx.sampled(resource).clone())
+        };
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("inspect"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.target_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, &input, &[]);
+        pass.set_bind_group(1, &self.params_bind_group, &[]);
+        pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        pass.draw(0..6, 0..1);
+    }
+}
+
+/// Creates, or resizes, the texture the inspector draws into and returns its egui id.
+pub fn inspector_init(render_state: &egui_wgpu::RenderState, size: (u32, u32)) -> egui::TextureId {
+    let device = &render_state.device;
+    let target = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("inspect_target"),
+        size: wgpu::Extent3d {
+            width: size.0.max(1),
+            height: size.1.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: INSPECT_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let target_view = target.create_view(&Default::default());
+
+    let mut renderer = render_state.renderer.write();
+    let existing = renderer
+        .callback_resources
+        .get::<ObjectRenderResources>()
+        .and_then(|resources| resources.inspector.as_ref())
+        .map(|inspector| inspector.texture_id);
+    let texture_id = match existing {
+        Some(id) => {
+            renderer.update_egui_texture_from_wgpu_texture(
+                device,
+                &target_view,
+                wgpu::FilterMode::Nearest,
+                id,
+            );
+            id
+        }
+        None => renderer.register_native_texture(device, &target_view, wgpu::FilterMode::Nearest),
+    };
+
+    let resources = renderer
+        .callback_resources
+        .get_mut::<ObjectRenderResources>()
+        .unwrap();
+    match &mut resources.inspector {
+        Some(inspector) => inspector.target_view = target_view,
+        None => resources.inspector = Some(TextureInspector::new(device, target_view, texture_id)),
+    }
+    texture_id
+}
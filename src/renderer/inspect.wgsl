@@ -0,0 +1,37 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+struct InspectParams {
+    inv_proj: mat4x4<f32>,
+    channel: u32,
+    linearize_depth: u32,
+};
+
+@group(0) @binding(0) var inspect_texture: texture_2d<f32>;
+@group(0) @binding(1) var inspect_sampler: sampler;
+@group(1) @binding(0) var<uniform> params: InspectParams;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(inspect_texture, inspect_sampler, in.uv);
+    switch params.channel {
+        case 1u: { return vec4<f32>(color.rrr, 1.0); }
+        case 2u: { return vec4<f32>(color.ggg, 1.0); }
+        case 3u: { return vec4<f32>(color.bbb, 1.0); }
+        case 4u: { return vec4<f32>(color.aaa, 1.0); }
+        default: { return vec4<f32>(color.rgb, 1.0); }
+    }
+}
@@ -0,0 +1,41 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+struct InspectParams {
+    inv_proj: mat4x4<f32>,
+    channel: u32,
+    linearize_depth: u32,
+};
+
+@group(0) @binding(0) var depth_texture: texture_depth_2d;
+@group(1) @binding(0) var<uniform> params: InspectParams;
+
+fn view_depth(depth: f32) -> f32 {
+    let view = params.inv_proj * vec4<f32>(0.0, 0.0, depth, 1.0);
+    return view.z / view.w;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let dims = vec2<i32>(textureDimensions(depth_texture));
+    let coords = min(vec2<i32>(in.uv * vec2<f32>(dims)), dims - 1);
+    var depth = textureLoad(depth_texture, coords, 0);
+    if params.linearize_depth != 0u {
+        // Distance along the view axis as a fraction of the far plane.
+        depth = view_depth(depth) / view_depth(1.0);
+    }
+    return vec4<f32>(vec3<f32>(depth), 1.0);
+}
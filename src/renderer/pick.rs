@@ -0,0 +1,122 @@
+//! Reads back the scene depth under the cursor to find the world position there.
+
+use std::sync::{Arc, Mutex};
+
+use eframe::egui_wgpu::{self, wgpu};
+
+use super::ObjectRenderResources;
+
+pub struct DepthPicker {
+    buffer: wgpu::Buffer,
+    /// Inverse view-projection and NDC position of the read in flight.
+    pending: Option<(glam::Mat4, glam::Vec2)>,
+    /// Set once a copy is recorded and cleared when the buffer is mapped.
+    copied: bool,
+    /// Filled in by the map callback with whether mapping succeeded.
+    mapped: Arc<Mutex<Option<bool>>>,
+}
+
+impl DepthPicker {
+    pub fn new(device: &wgpu::Device) -> Self {
+        Self {
+            buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("depth_pick"),
+                size: wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }),
+            pending: None,
+            copied: false,
+            mapped: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.pending.is_none()
+    }
+
+    /// Records the copy of the depth texel at `pixel`, from a pass reading the depth texture.
+    pub fn copy(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        depth: &wgpu::Texture,
+        pixel: [u32; 2],
+        inv_view_proj: glam::Mat4,
+    ) {
+        let size = depth.size();
+        let x = pixel[0].min(size.width - 1);
+        let y = pixel[1].min(size.height - 1);
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: depth,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::DepthOnly,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &self.buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: None,
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        let ndc = glam::vec2(
+            (x as f32 + 0.5) / size.width as f32 * 2.0 - 1.0,
+            1.0 - (y as f32 + 0.5) / size.height as f32 * 2.0,
+        );
+        self.pending = Some((inv_view_proj, ndc));
+        self.copied = true;
+    }
+
+    /// Starts mapping the buffer if a copy was recorded. Call after submitting it.
+    pub fn map(&mut self) {
+        if !std::mem::take(&mut self.copied) {
+            return;
+        }
+        let mapped = self.mapped.clone();
+        self.buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                *mapped.lock().unwrap() = Some(result.is_ok());
+            });
+    }
+
+    /// The world position of a finished read, if it hit anything.
+    fn take_hit(&mut self) -> Option<glam::Vec3> {
+        let mapped = self.mapped.lock().unwrap().take()?;
+        let (inv_view_proj, ndc) = self.pending.take()?;
+        if !mapped {
+            return None;
+        }
+        let depth = {
+            let bytes = self.buffer.slice(..).get_mapped_range();
+            f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        };
+        self.buffer.unmap();
+
+        // The far plane is the cleared background.
+        (depth < 1.0).then(|| inv_view_proj.project_point3(ndc.extend(depth)))
+    }
+}
+
+/// World position under the last requested pick, once its depth has been read back.
+pub fn take_picked_point(render_state: &egui_wgpu::RenderState) -> Option<glam::Vec3> {
+    let mut renderer = render_state.renderer.write();
+    let picker = &mut renderer
+        .callback_resources
+        .get_mut::<ObjectRenderResources>()?
+        .picker;
+    if picker.is_idle() {
+        return None;
+    }
+    let _ = render_state.device.poll(wgpu::PollType::Poll);
+    picker.take_hit()
+}
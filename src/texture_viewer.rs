@@ -0,0 +1,147 @@
+use crate::{
+    config::PostEffectEntry,
+    renderer::{
+        self,
+        inspect::{InspectChannel, InspectRequest},
+    },
+};
+
+/// Debug window showing any intermediate render target of the current frame.
+pub struct TextureViewer {
+    pub open: bool,
+    texture: Option<egui::TextureId>,
+    aspect: f32,
+    selected: String,
+    channel: InspectChannel,
+    linearize_depth: bool,
+    zoom: f32,
+    /// Centre of the visible region, in uv space.
+    center: egui::Vec2,
+}
+
+impl TextureViewer {
+    pub fn new() -> TextureViewer {
+        TextureViewer {
+            open: false,
+            texture: None,
+            aspect: 1.0,
+            selected: renderer::SCENE_COLOR.to_string(),
+            channel: InspectChannel::Rgb,
+            linearize_depth: true,
+            zoom: 1.0,
+            center: egui::vec2(0.5, 0.5),
+        }
+    }
+
+    pub fn set_texture(&mut self, texture: egui::TextureId, size: (u32, u32)) {
+        self.texture = Some(texture);
+        self.aspect = size.0.max(1) as f32 / size.1.max(1) as f32;
+    }
+
+    pub fn request(&self) -> Option<InspectRequest> {
+        (self.open && self.texture.is_some()).then(|| InspectRequest {
+            resource: self.selected.clone(),
+            channel: self.channel,
+            linearize_depth: self.linearize_depth,
+        })
+    }
+
+    /// Graph textures in execution order, as (label, graph name). Effect histories,
+    /// such as TAA's, are listed before the effect that reads them.
+    fn targets(effects: &[PostEffectEntry]) -> Vec<(String, String)> {
+        let mut targets = vec![
+            (
+                "Scene colour".to_string(),
+                renderer::SCENE_COLOR.to_string(),
+            ),
+            ("Scene depth".to_string(), renderer::SCENE_DEPTH.to_string()),
+            (
+                "Scene normals".to_string(),
+                renderer::SCENE_NORMALS.to_string(),
+            ),
+        ];
+        for (i, entry) in effects.iter().filter(|entry| entry.enabled).enumerate() {
+            let effect = entry.effect.lock().unwrap();
+            let name = effect.as_enum();
+            if effect.history().is_some() {
+                targets.push((
+                    format!("{}: {name} history", i + 1),
+                    renderer::post_history_name(i),
+                ));
+            }
+            targets.push((format!("{}: {name}", i + 1), renderer::post_output_name(i)));
+        }
+        targets
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, effects: &[PostEffectEntry]) {
+        let targets = Self::targets(effects);
+        if !targets.iter().any(|(_, name)| *name == self.selected) {
+            self.selected = renderer::SCENE_COLOR.to_string();
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Texture viewer")
+            .open(&mut open)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                let selected_label = targets
+                    .iter()
+                    .find(|(_, name)| *name == self.selected)
+                    .map(|(label, _)| label.as_str())
+                    .unwrap_or_default();
+                egui::ComboBox::from_label("Render target")
+                    .selected_text(selected_label)
+                    .show_ui(ui, |ui| {
+                        for (label, name) in &targets {
+                            ui.selectable_value(&mut self.selected, name.clone(), label);
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    for channel in InspectChannel::ALL {
+                        ui.selectable_value(&mut self.channel, channel, channel.to_string());
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut self.linearize_depth, "Linearize depth");
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(format!("zoom: {:.1}x", self.zoom));
+                    if ui.button("Reset view").clicked() {
+                        self.zoom = 1.0;
+                        self.center = egui::vec2(0.5, 0.5);
+                    }
+                });
+
+                let Some(texture) = self.texture else {
+                    return;
+                };
+                let width = ui.available_width();
+                let size = egui::vec2(width, width / self.aspect);
+                let half = egui::Vec2::splat(0.5 / self.zoom);
+                let uv = egui::Rect::from_min_max(
+                    (self.center - half).to_pos2(),
+                    (self.center + half).to_pos2(),
+                );
+                let response = ui.add(
+                    egui::Image::new(egui::load::SizedTexture::new(texture, size))
+                        .uv(uv)
+                        .sense(egui::Sense::drag()),
+                );
+
+                if response.dragged() {
+                    self.center -= response.drag_delta() / size / self.zoom;
+                }
+                if response.hovered() {
+                    let scroll = ui.input(|i| i.smooth_scroll_delta.y);
+                    self.zoom = (self.zoom * (scroll * 0.01).exp()).clamp(1.0, 64.0);
+                }
+                // Keep the visible region inside the texture.
+                let half = 0.5 / self.zoom;
+                self.center.x = self.center.x.clamp(half, 1.0 - half);
+                self.center.y = self.center.y.clamp(half, 1.0 - half);
+            });
+        self.open = open;
+    }
+}
@@ -0,0 +1,79 @@
+//! Noticing when the files a mesh was read from change on disk.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use notify::{EventKind, RecursiveMode, Result, Watcher as _, event::ModifyKind};
+
+/// How long the files have to stay untouched before a change counts. Exporters tend
+/// to write a file in several steps, or several files one after the other.
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// Watches the directories holding `files` rather than the files themselves, so files
+/// replaced by a rename are still followed.
+pub struct FileWatcher {
+    _watcher: notify::RecommendedWatcher,
+    events: mpsc::Receiver<Result<notify::Event>>,
+    files: Vec<PathBuf>,
+    /// When the last change to one of `files` was seen.
+    changed: Option<Instant>,
+}
+
+impl FileWatcher {
+    pub fn new(files: &[PathBuf]) -> Result<FileWatcher> {
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+
+        // Events name files under the canonical directory path.
+        let files: Vec<PathBuf> = files
+            .iter()
+            .filter_map(|file| {
+                let directory = file.parent().unwrap_or(Path::new("."));
+                let directory = directory
+                    .canonicalize()
+                    .unwrap_or_else(|_| directory.to_path_buf());
+                Some(directory.join(file.file_name()?))
+            })
+            .collect();
+        let mut directories: Vec<&Path> = files.iter().filter_map(|file| file.parent()).collect();
+        directories.sort();
+        directories.dedup();
+        for directory in directories {
+            watcher.watch(directory, RecursiveMode::NonRecursive)?;
+        }
+
+        Ok(FileWatcher {
+            _watcher: watcher,
+            events,
+            files,
+            changed: None,
+        })
+    }
+
+    /// Whether the files changed and have since settled. Reports each change once.
+    pub fn poll(&mut self) -> bool {
+        for event in self.events.try_iter().flatten() {
+            // Reading the files, the reload included, isn't a change.
+            let writes = match event.kind {
+                EventKind::Modify(ModifyKind::Metadata(_)) => false,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => true,
+                _ => false,
+            };
+            if writes && event.paths.iter().any(|path| self.files.contains(path)) {
+                self.changed = Some(Instant::now());
+            }
+        }
+
+        if self
+            .changed
+            .is_some_and(|changed| changed.elapsed() >= SETTLE_TIME)
+        {
+            self.changed = None;
+            return true;
+        }
+        false
+    }
+}
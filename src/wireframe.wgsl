@@ -0,0 +1,65 @@
+struct CameraUniform {
+    proj: mat4x4<f32>,
+    view: mat4x4<f32>,
+    inv_proj: mat4x4<f32>,
+    inv_view: mat4x4<f32>,
+    view_proj: mat4x4<f32>,
+    prev_view_proj: mat4x4<f32>,
+    jitter: vec4<f32>,
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct ModelUniform {
+    model: mat4x4<f32>,
+    normal: mat3x3<f32>,
+};
+@group(0) @binding(1)
+var<uniform> model: ModelUniform;
+
+struct Wireframe {
+    color: vec4<f32>,
+    width: f32,
+}
+@group(1) @binding(0)
+var<uniform> wireframe: Wireframe;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) barycentric: vec3<f32>,
+};
+
+// The scene is drawn as a plain triangle list, so every third vertex starts a triangle.
+// `edges` has a bit for each edge to draw; the others are pushed a whole triangle away.
+@vertex
+fn vs_main(
+    @builtin(vertex_index) index: u32,
+    @location(0) position: vec3<f32>,
+    @location(5) edges: u32,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = camera.proj * camera.view * model.model * vec4<f32>(position, 1.0);
+    let corner = index % 3u;
+    out.barycentric = vec3<f32>(
+        f32(corner == 0u),
+        f32(corner == 1u),
+        f32(corner == 2u),
+    ) + vec3<f32>(
+        f32((edges & 1u) == 0u),
+        f32((edges & 2u) == 0u),
+        f32((edges & 4u) == 0u),
+    );
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Distance to the nearest edge in pixels, via screen-space derivatives.
+    let pixels = in.barycentric / fwidth(in.barycentric);
+    let distance = min(min(pixels.x, pixels.y), pixels.z);
+    let coverage = 1.0 - smoothstep(wireframe.width - 0.5, wireframe.width + 0.5, distance);
+    if coverage <= 0.0 {
+        discard;
+    }
+    return vec4<f32>(wireframe.color.rgb, wireframe.color.a * coverage);
+}